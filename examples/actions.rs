@@ -0,0 +1,58 @@
+use winit::keyboard::KeyCode;
+use yugin::{ActionMap, Axis2DBinding, Colors, Game, InputManager, MouseButton, Renderer, Vector2};
+
+const SPEED: f32 = 250.0;
+const BINDINGS_PATH: &str = "bindings.toml";
+
+struct ActionsDemo {
+    actions: ActionMap,
+    position: Vector2,
+    size: f32,
+    jumping: f32,
+}
+
+impl Default for ActionsDemo {
+    fn default() -> Self {
+        let mut defaults = ActionMap::new();
+        defaults
+            .bind_axis_2d("move", Axis2DBinding::wasd())
+            .bind_axis_2d("move", Axis2DBinding::arrows())
+            .bind_axis_buttons("grow", KeyCode::KeyQ, KeyCode::KeyE)
+            .bind("jump", KeyCode::Space)
+            .bind("jump", MouseButton::Left);
+
+        // Edit bindings.toml to rebind, delete it to restore defaults
+        let actions = ActionMap::load_or(BINDINGS_PATH, &defaults);
+        if let Err(e) = actions.save(BINDINGS_PATH) {
+            eprintln!("{e}");
+        }
+        for conflict in actions.conflicts() {
+            println!("{:?} is bound to {:?}", conflict.binding, conflict.actions);
+        }
+
+        Self { actions, position: Vector2::new(200.0, 200.0), size: 50.0, jumping: 0.0 }
+    }
+}
+
+impl Game for ActionsDemo {
+    fn update(&mut self, delta_time: f32, input: &mut InputManager) {
+        self.position = self.position + self.actions.axis_2d(input, "move") * SPEED * delta_time;
+        self.size = (self.size + self.actions.axis(input, "grow") * 100.0 * delta_time).clamp(10.0, 200.0);
+
+        if self.actions.is_pressed(input, "jump") {
+            self.jumping = 0.3;
+        }
+        self.jumping = (self.jumping - delta_time).max(0.0);
+    }
+
+    fn draw(&mut self, renderer: &mut Renderer, _alpha: f32) {
+        let color = if self.jumping > 0.0 { Colors::Yellow } else { Colors::Green };
+        renderer.draw_rectangle(self.position, self.size, self.size, color);
+    }
+}
+
+fn main() {
+    if let Err(e) = yugin::run(ActionsDemo::default()) {
+        eprintln!("{e}");
+    }
+}
@@ -0,0 +1,43 @@
+use winit::keyboard::KeyCode::{ArrowDown, ArrowRight, KeyJ, KeyS};
+use yugin::{Chord, ComboDetector, Game, InputManager, Renderer, Sequence};
+
+struct Combos {
+    detector: ComboDetector,
+}
+
+impl Default for Combos {
+    fn default() -> Self {
+        let mut detector = ComboDetector::new();
+        detector
+            .add_chord("save", Chord::new().ctrl().with(KeyS).exclusive())
+            .add_chord("save_as", Chord::new().ctrl().shift().with(KeyS).exclusive())
+            .add_sequence(
+                "fireball",
+                Sequence::new(0.5)
+                    .then(Chord::new().with(ArrowDown).exclusive())
+                    .then(Chord::new().with(ArrowDown).with(ArrowRight).exclusive())
+                    .then(Chord::new().with(ArrowRight).exclusive())
+                    .then(KeyJ),
+            );
+
+        Self { detector }
+    }
+}
+
+impl Game for Combos {
+    fn update(&mut self, delta_time: f32, input: &mut InputManager) {
+        self.detector.update(input, delta_time);
+
+        for event in self.detector.events() {
+            println!("{:?} {} at {:.2}s", event.kind, event.name, event.time);
+        }
+    }
+
+    fn draw(&mut self, _renderer: &mut Renderer, _alpha: f32) {}
+}
+
+fn main() {
+    if let Err(e) = yugin::run(Combos::default()) {
+        eprintln!("{e}");
+    }
+}
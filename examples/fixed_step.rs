@@ -0,0 +1,42 @@
+use yugin::{Colors, FrameConfig, Game, InputManager, PresentMode, Renderer, TimeStep, Vector2};
+
+/// Moves a square at 20 ticks per second and interpolates between ticks when drawing,
+/// which runs at up to 120 frames per second without vsync
+#[derive(Default)]
+struct FixedStep {
+    previous: Vector2,
+    position: Vector2,
+    velocity: f32,
+}
+
+impl Game for FixedStep {
+    fn time_step(&self) -> TimeStep {
+        TimeStep::fixed(20.0)
+    }
+
+    fn frame_config(&self) -> FrameConfig {
+        FrameConfig { present_mode: PresentMode::Immediate, target_fps: Some(120.0), ..Default::default() }
+    }
+
+    fn update(&mut self, delta_time: f32, _input: &mut InputManager) {
+        self.previous = self.position;
+        self.position.x += self.velocity * delta_time;
+
+        if self.position.x > 600.0 {
+            self.position.x = 0.0;
+            self.previous = self.position;
+        }
+    }
+
+    fn draw(&mut self, renderer: &mut Renderer, alpha: f32) {
+        let position = self.previous + (self.position - self.previous) * alpha;
+        renderer.draw_rectangle(Vector2::new(position.x, 100.0), 40.0, 40.0, Colors::White);
+    }
+}
+
+fn main() {
+    let game = FixedStep { velocity: 200.0, ..Default::default() };
+    if let Err(e) = yugin::run(game) {
+        eprintln!("{e}");
+    }
+}
@@ -0,0 +1,56 @@
+use yugin::{BlendMode, Colors, OffscreenTarget, Rect, Renderer, Vector2};
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 240;
+
+fn main() {
+    let mut renderer = match pollster::block_on(Renderer::headless(WIDTH, HEIGHT, 10_000)) {
+        Ok(renderer) => renderer,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let target = OffscreenTarget::new(&renderer);
+
+    let pixels: Vec<u8> = (0..16)
+        .flat_map(|i| if (i % 4 + i / 4) % 2 == 0 { [255, 255, 255, 255] } else { [0, 0, 0, 255] })
+        .collect();
+    let checker = match renderer.create_texture(4, 4, &pixels) {
+        Ok(texture) => texture,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+
+    renderer.begin_frame();
+    renderer.draw_rectangle(Vector2::new(10.0, 10.0), 100.0, 60.0, Colors::Red);
+    renderer.draw_triangle(Vector2::new(130.0, 70.0), Vector2::new(180.0, 10.0), Vector2::new(230.0, 70.0), Colors::Green);
+    renderer.draw_circle(Vector2::new(60.0, 160.0), 50.0, 32, Colors::Blue);
+    renderer.draw_sprite(checker, Vector2::new(150.0, 110.0), Vector2::new(120.0, 120.0), Some(Rect::new(0.0, 0.0, 2.0, 2.0)), Colors::White, 0.0);
+    renderer.draw_rectangle(Vector2::new(60.0, 40.0), 100.0, 60.0, Colors::Custom(255, 255, 0, 128));
+    renderer.set_blend_mode(BlendMode::Additive);
+    renderer.draw_circle(Vector2::new(100.0, 160.0), 40.0, 32, Colors::Custom(255, 0, 0, 255));
+    renderer.set_blend_mode(BlendMode::Alpha);
+
+    // Tank body with a turret drawn relative to it
+    renderer.push_transform();
+    renderer.translate(Vector2::new(280.0, 40.0));
+    renderer.rotate(0.5);
+    renderer.draw_rectangle(Vector2::new(-20.0, -12.0), 40.0, 24.0, Colors::Green);
+    renderer.push_transform();
+    renderer.rotate(-1.2);
+    renderer.draw_rectangle(Vector2::new(0.0, -3.0), 28.0, 6.0, Colors::White);
+    renderer.pop_transform();
+    renderer.draw_circle(Vector2::zero(), 8.0, 16, Colors::White);
+    renderer.pop_transform();
+
+    renderer.end_frame_offscreen(&target);
+
+    let path = std::env::args().nth(1).unwrap_or_else(|| "capture.png".to_string());
+    match target.save_png(&renderer, &path) {
+        Ok(()) => println!("Saved {path}"),
+        Err(e) => eprintln!("{e}"),
+    }
+}
@@ -0,0 +1,60 @@
+use winit::keyboard::KeyCode::{F5, F6, KeyW};
+use yugin::{Colors, Game, InputManager, InputRecording, Key, Renderer, Vector2};
+
+const REPLAY_PATH: &str = "replay.toml";
+
+#[derive(Default)]
+struct Demo {
+    show_rectangle: bool,
+}
+
+impl Game for Demo {
+    fn update(&mut self, _delta_time: f32, input_manager: &mut InputManager) {
+        self.show_rectangle = input_manager.is_held(Key::Code(KeyW));
+
+        if self.show_rectangle {
+            println!("W held");
+        }
+
+        if input_manager.is_pressed(Key::Code(KeyW)) {
+            println!("W pressed");
+        }
+
+        if input_manager.is_released(Key::Code(KeyW)) {
+            println!("W released");
+        }
+
+        // F5 starts and stops recording, F6 replays the last recording
+        if input_manager.is_pressed(Key::Code(F5)) {
+            match input_manager.stop_recording() {
+                Some(recording) => match recording.save(REPLAY_PATH) {
+                    Ok(()) => println!("Saved {REPLAY_PATH}"),
+                    Err(e) => eprintln!("{e}"),
+                },
+                None => {
+                    println!("Recording");
+                    input_manager.start_recording();
+                }
+            }
+        }
+
+        if input_manager.is_pressed(Key::Code(F6)) && !input_manager.is_recording() {
+            match InputRecording::load(REPLAY_PATH) {
+                Ok(recording) => input_manager.start_replay(recording),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+    }
+
+    fn draw(&mut self, renderer: &mut Renderer, _alpha: f32) {
+        if self.show_rectangle {
+            renderer.draw_rectangle(Vector2::new(100.0, 100.0), 200.0, 150.0, Colors::Red)
+        }
+    }
+}
+
+fn main() {
+    if let Err(e) = yugin::run(Demo::default()) {
+        eprintln!("{e}");
+    }
+}
@@ -0,0 +1,68 @@
+use winit::keyboard::KeyCode::{F11, KeyG};
+use yugin::{Colors, Game, GameWindow, InputManager, Key, MouseButton, Renderer, Vector2, WindowConfig};
+
+struct MouseDemo {
+    window: Option<GameWindow>,
+    cursor: Vector2,
+    radius: f32,
+    color: Colors,
+    grabbed: bool,
+}
+
+impl Default for MouseDemo {
+    fn default() -> Self {
+        Self { window: None, cursor: Vector2::zero(), radius: 20.0, color: Colors::White, grabbed: false }
+    }
+}
+
+impl Game for MouseDemo {
+    fn window_config(&self) -> WindowConfig {
+        WindowConfig::new("Mouse").size(800, 600).min_size(320, 240)
+    }
+
+    fn init(&mut self, _renderer: &mut Renderer, window: &GameWindow) {
+        self.window = Some(window.clone());
+    }
+
+    fn update(&mut self, _delta_time: f32, input: &mut InputManager) {
+        self.cursor = input.cursor_position();
+        self.radius = (self.radius + input.scroll().y * 4.0).clamp(4.0, 200.0);
+
+        self.color = if input.is_mouse_held(MouseButton::Left) {
+            Colors::Red
+        } else if input.is_mouse_held(MouseButton::Right) {
+            Colors::Blue
+        } else {
+            Colors::White
+        };
+
+        if input.is_mouse_pressed(MouseButton::Middle) {
+            println!("Middle click at {:?}", self.cursor);
+        }
+
+        if input.is_pressed(Key::Code(KeyG)) {
+            self.grabbed = !self.grabbed;
+            input.set_cursor_grab(self.grabbed);
+            input.set_cursor_visible(!self.grabbed);
+        }
+
+        if input.is_pressed(Key::Code(F11)) && let Some(window) = &self.window {
+            window.toggle_fullscreen();
+            window.set_title(&format!("Mouse ({:?})", window.fullscreen()));
+        }
+
+        if self.grabbed && input.mouse_motion() != Vector2::zero() {
+            println!("Motion {:?}", input.mouse_motion());
+        }
+    }
+
+    fn draw(&mut self, renderer: &mut Renderer, _alpha: f32) {
+        renderer.draw_circle(self.cursor, self.radius, 32, self.color);
+    }
+}
+
+fn main() {
+    if let Err(e) = yugin::run(MouseDemo::default()) {
+        eprintln!("{e}");
+    }
+}
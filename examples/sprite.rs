@@ -0,0 +1,45 @@
+use yugin::{Colors, Game, GameWindow, InputManager, Rect, Renderer, Texture, Vector2};
+
+#[derive(Default)]
+struct SpriteDemo {
+    checker: Option<Texture>,
+    rotation: f32,
+}
+
+impl Game for SpriteDemo {
+    fn init(&mut self, renderer: &mut Renderer, _window: &GameWindow) {
+        // 8x8 checkerboard generated on the CPU
+        let pixels: Vec<u8> = (0..64)
+            .flat_map(|i| if (i % 8 + i / 8) % 2 == 0 { [255, 255, 255, 255] } else { [40, 40, 40, 255] })
+            .collect();
+        match renderer.create_texture(8, 8, &pixels) {
+            Ok(texture) => self.checker = Some(texture),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+
+    fn update(&mut self, delta_time: f32, _input: &mut InputManager) {
+        self.rotation += delta_time;
+    }
+
+    fn draw(&mut self, renderer: &mut Renderer, _alpha: f32) {
+        let Some(checker) = self.checker else { return };
+
+        renderer.draw_rectangle(Vector2::new(20.0, 20.0), 100.0, 100.0, Colors::Blue);
+        renderer.draw_sprite(checker, Vector2::new(150.0, 20.0), Vector2::new(128.0, 128.0), None, Colors::White, 0.0);
+        renderer.draw_sprite(
+            checker,
+            Vector2::new(320.0, 20.0),
+            Vector2::new(128.0, 128.0),
+            Some(Rect::new(0.0, 0.0, 4.0, 4.0)),
+            Colors::Orange,
+            self.rotation,
+        );
+    }
+}
+
+fn main() {
+    if let Err(e) = yugin::run(SpriteDemo::default()) {
+        eprintln!("{e}");
+    }
+}
@@ -0,0 +1,44 @@
+use yugin::{Colors, Font, Game, GameWindow, InputManager, Renderer, TextAlign, TextLayout, Vector2};
+
+struct TextDemo {
+    font_path: String,
+    font: Option<Font>,
+    elapsed: f32,
+}
+
+impl Game for TextDemo {
+    fn init(&mut self, renderer: &mut Renderer, _window: &GameWindow) {
+        match renderer.load_font(&self.font_path, 32.0) {
+            Ok(font) => self.font = Some(font),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+
+    fn update(&mut self, delta_time: f32, _input: &mut InputManager) {
+        self.elapsed += delta_time;
+    }
+
+    fn draw(&mut self, renderer: &mut Renderer, _alpha: f32) {
+        let Some(font) = &self.font else { return };
+
+        renderer.draw_text(font, &format!("Time: {:.1}s", self.elapsed), Vector2::new(20.0, 20.0), 32.0, Colors::White);
+
+        let layout = TextLayout { align: TextAlign::Center, max_width: Some(300.0) };
+        let text = "Long lines are wrapped at word boundaries and centered inside the box";
+        let size = font.measure(text, 20.0, layout.max_width);
+        renderer.draw_rectangle(Vector2::new(20.0, 80.0), 300.0, size.y, Colors::Custom(255, 255, 255, 40));
+        renderer.draw_text_ex(font, text, Vector2::new(20.0, 80.0), 20.0, Colors::Yellow, layout);
+    }
+}
+
+fn main() {
+    let Some(font_path) = std::env::args().nth(1) else {
+        eprintln!("Usage: cargo run --example text -- <font.ttf>");
+        return;
+    };
+
+    let game = TextDemo { font_path, font: None, elapsed: 0.0 };
+    if let Err(e) = yugin::run(game) {
+        eprintln!("{e}");
+    }
+}
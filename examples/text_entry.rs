@@ -0,0 +1,58 @@
+use yugin::{Colors, Font, Game, GameWindow, InputManager, Renderer, Vector2};
+
+struct NameEntry {
+    font_path: String,
+    font: Option<Font>,
+    name: String,
+    preedit: String,
+    ime_enabled: bool,
+}
+
+impl Game for NameEntry {
+    fn init(&mut self, renderer: &mut Renderer, _window: &GameWindow) {
+        match renderer.load_font(&self.font_path, 32.0) {
+            Ok(font) => self.font = Some(font),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+
+    fn update(&mut self, _delta_time: f32, input: &mut InputManager) {
+        if !self.ime_enabled {
+            input.set_ime_allowed(true);
+            input.set_ime_cursor_area(Vector2::new(20.0, 60.0), Vector2::new(400.0, 40.0));
+            self.ime_enabled = true;
+        }
+
+        if input.edit_text(&mut self.name) {
+            println!("Hello, {}!", self.name);
+            self.name.clear();
+        }
+
+        self.preedit = input.preedit().map(|p| p.text.clone()).unwrap_or_default();
+    }
+
+    fn draw(&mut self, renderer: &mut Renderer, _alpha: f32) {
+        let Some(font) = &self.font else { return };
+
+        renderer.draw_text(font, "Enter your name:", Vector2::new(20.0, 20.0), 24.0, Colors::White);
+        renderer.draw_rectangle(Vector2::new(20.0, 60.0), 400.0, 40.0, Colors::Custom(255, 255, 255, 40));
+
+        let position = Vector2::new(28.0, 64.0);
+        renderer.draw_text(font, &self.name, position, 28.0, Colors::White);
+
+        let offset = font.line_width(&self.name, 28.0);
+        renderer.draw_text(font, &self.preedit, Vector2::new(position.x + offset, position.y), 28.0, Colors::Yellow);
+    }
+}
+
+fn main() {
+    let Some(font_path) = std::env::args().nth(1) else {
+        eprintln!("Usage: cargo run --example text_entry -- <font.ttf>");
+        return;
+    };
+
+    let game = NameEntry { font_path, font: None, name: String::new(), preedit: String::new(), ime_enabled: false };
+    if let Err(e) = yugin::run(game) {
+        eprintln!("{e}");
+    }
+}
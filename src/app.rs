@@ -1,14 +1,19 @@
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use winit::application::ApplicationHandler;
 use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, WindowEvent, KeyEvent, StartCause};
+use winit::event::{DeviceEvent, DeviceId, WindowEvent, KeyEvent};
 use winit::event::WindowEvent::KeyboardInput;
-use winit::event_loop::ActiveEventLoop;
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
 use winit::window::{Window, WindowId};
+use crate::engine::error::EngineError;
 use crate::engine::renderer::renderer::Renderer;
-use crate::render_logic::draw_scene;
+use crate::game::Game;
 use crate::engine::events::keyboard::ButtonState::InputManager;
+use crate::engine::time::frame_config::{FrameConfig, PresentMode, RedrawMode};
+use crate::engine::time::time_step::StepTimer;
+use crate::engine::window::game_window::GameWindow;
+use crate::engine::window::window_config::WindowConfig;
 
 pub struct State<'a> {
     surface: wgpu::Surface<'a>,
@@ -17,32 +22,39 @@ pub struct State<'a> {
 }
 
 impl<'a> State<'a> {
-    pub async fn new(window: Arc<Window>) -> Self {
+    pub async fn new(window: Arc<Window>, present_mode: PresentMode) -> Result<Self, EngineError> {
         let instance = wgpu::Instance::default();
-        let surface = instance.create_surface(window.clone()).unwrap();
+        let surface = instance.create_surface(window.clone())?;
 
         let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
             power_preference: wgpu::PowerPreference::HighPerformance,
             compatible_surface: Some(&surface),
             force_fallback_adapter: false,
-        }).await.unwrap();
+        }).await.ok_or_else(|| EngineError::no_adapter(&instance))?;
 
         let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
             label: None,
             required_features: Default::default(),
             required_limits: Default::default(),
-        }, None).await.unwrap();
+        }, None).await?;
 
         let caps = surface.get_capabilities(&adapter);
-        let format = caps.formats[0];
+        let format = caps.formats.first().copied().ok_or_else(|| EngineError::NoSurfaceFormat {
+            adapter: adapter.get_info().name,
+        })?;
         let size = window.inner_size();
 
+        let selected_mode = present_mode.select(&caps.present_modes);
+        if selected_mode != present_mode.to_wgpu() {
+            println!("Present mode {present_mode:?} is not supported, using {selected_mode:?}");
+        }
+
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format,
             width: size.width,
             height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
+            present_mode: selected_mode,
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
@@ -50,30 +62,40 @@ impl<'a> State<'a> {
 
         surface.configure(&device, &config);
 
-        window.set_title("Latent");
-
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shape Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("engine/renderer/shaders/shader.wgsl").into()),
-        });
+        let shader = Renderer::create_shader(&device);
 
         let size = window.inner_size();
 
         let renderer = Renderer::new(device, queue, shader, format, size.width, size.height, 10_000);
 
-        Self { surface, config, renderer }
+        Ok(Self { surface, config, renderer })
     }
 
+    /// Reconfigures the surface for the new size. A minimized window reports a 0 size,
+    /// which is remembered without configuring the surface until the window is restored
     pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
         if new_size.width == 0 || new_size.height == 0 {
+            self.config.width = 0;
+            self.config.height = 0;
             return;
         }
-        
+
         self.config.width = new_size.width;
         self.config.height = new_size.height;
         self.surface.configure(&self.renderer.device, &self.config);
-        self.renderer.surface_config.width = new_size.width;
-        self.renderer.surface_config.height = new_size.height;
+        self.renderer.resize(new_size.width, new_size.height);
+    }
+
+    /// Configures the surface again with the current settings, after it was lost or became outdated
+    pub fn reconfigure(&mut self) {
+        if !self.is_minimized() {
+            self.surface.configure(&self.renderer.device, &self.config);
+        }
+    }
+
+    /// Whether the surface has no area, so nothing can be drawn
+    pub fn is_minimized(&self) -> bool {
+        self.config.width == 0 || self.config.height == 0
     }
 
     pub fn draw(&self) {
@@ -81,78 +103,203 @@ impl<'a> State<'a> {
     }
 }
 
-pub struct App<'a>{
+pub struct App<'a, G: Game>{
     context: AppContext<'a>,
+    game: G,
+    error: Option<EngineError>,
+    /// Surface error kinds already printed, each is only logged the first time
+    logged_surface_errors: Vec<wgpu::SurfaceError>,
 }
 
-impl<'a> Default for App<'a> {
-    fn default() -> Self {
+impl<'a, G: Game> App<'a, G> {
+    pub fn new(game: G) -> Self {
+        let mut context = AppContext::new();
+        context.step_timer = StepTimer::new(game.time_step());
+        context.frame_config = game.frame_config();
+        context.window_config = game.window_config();
+
         Self {
-            context: AppContext::new(),
+            context,
+            game,
+            error: None,
+            logged_surface_errors: Vec::new(),
         }
     }
+
+    fn handle_surface_error(&mut self, error: wgpu::SurfaceError, event_loop: &ActiveEventLoop) {
+        if !self.logged_surface_errors.contains(&error) {
+            eprintln!("Surface error: {error}, repeats of it won't be logged");
+            self.logged_surface_errors.push(error.clone());
+        }
+        self.game.on_surface_error(&error);
+
+        match error {
+            wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => {
+                // A minimized window can't be configured, drawing resumes once it's restored
+                let Some(state) = &mut self.context.state else { return };
+                if state.is_minimized() {
+                    return;
+                }
+                state.reconfigure();
+                if let Some(window) = &self.context.window {
+                    window.request_redraw();
+                }
+            }
+            // The frame is skipped, the next one usually succeeds
+            wgpu::SurfaceError::Timeout => {}
+            wgpu::SurfaceError::OutOfMemory => {
+                self.error = Some(EngineError::OutOfMemory);
+                event_loop.exit();
+            }
+        }
+    }
+
+    /// Setup failure that made the app exit, if any
+    pub fn take_error(&mut self) -> Option<EngineError> {
+        self.error.take()
+    }
+}
+
+impl<'a, G: Game + Default> Default for App<'a, G> {
+    fn default() -> Self {
+        Self::new(G::default())
+    }
 }
 
-impl<'a> ApplicationHandler for App<'a> {
+impl<'a, G: Game> ApplicationHandler for App<'a, G> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         println!("App resumed");
         if self.context.window.is_none() {
-            let window = Arc::new(event_loop.create_window(Window::default_attributes()).unwrap());
-            self.context.window = Some(window.clone());
+            if let Err(e) = self.context.initalize(event_loop) {
+                self.error = Some(e);
+                event_loop.exit();
+                return;
+            }
 
-            let state = pollster::block_on(State::new(window.clone()));
-            self.context.state = Some(state);
+            if let (Some(state), Some(window)) = (&mut self.context.state, &self.context.window) {
+                self.game.init(&mut state.renderer, &GameWindow::new(window.clone()));
+            }
         }
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
-        if id != self.context.window.as_ref().unwrap().id() {
+        let Some(window) = self.context.window.clone() else { return };
+        if id != window.id() {
             return;
         }
 
+        self.game.on_event(&event);
+
+        if self.context.frame_config.redraw_mode == RedrawMode::OnDemand && !matches!(event, WindowEvent::RedrawRequested) {
+            window.request_redraw();
+        }
+
         match event {
             WindowEvent::CloseRequested => {
                 println!("Close requested");
                 event_loop.exit()
             },
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                println!("Scale factor changed to {scale_factor}");
+                self.context.apply_scale_factor(scale_factor);
+            },
             WindowEvent::Resized(physical_size) => {
                 println!("Resize requested");
-                self.context.state.as_mut().unwrap().resize(physical_size);
+                if let Some(state) = &mut self.context.state {
+                    let was_minimized = state.is_minimized();
+                    state.resize(physical_size);
+
+                    // Time spent minimized isn't simulated
+                    if was_minimized && !state.is_minimized() {
+                        self.context.last_frame_time = Instant::now();
+                        window.request_redraw();
+                    }
+                }
             },
             WindowEvent::RedrawRequested => {
                 let now = Instant::now();
                 let delta = now.duration_since(self.context.last_frame_time);
                 self.context.last_frame_time = now;
-                let delta_seconds = delta.as_secs_f32();
+                self.context.schedule_next_frame(now);
+                let (step, steps, alpha) = self.context.update(delta.as_secs_f32());
 
                 // Update Draw, Inputs etc
-                self.context.update(delta_seconds);
-                self.context.draw();
-                
-                self.context.window.as_mut().unwrap().request_redraw();
+                for tick in 0..steps {
+                    if tick > 0 {
+                        self.context.input_manager.settle();
+                    }
+                    self.game.update(step, &mut self.context.input_manager);
+                }
+                if let Err(e) = self.context.draw(&mut self.game, alpha) {
+                    self.handle_surface_error(e, event_loop);
+                }
             },
             KeyboardInput {
                 event:
                 KeyEvent {
                     physical_key,
+                    logical_key,
+                    text,
                     state,
                     ..
                 },
                 ..
             } => {
                 self.context.input_manager.handle_key(physical_key, state);
+                self.context.input_manager.handle_text_key(&logical_key, text.as_deref(), state);
+            }
+            WindowEvent::Ime(ime) => {
+                self.context.input_manager.handle_ime(ime);
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.context.input_manager.handle_mouse_button(button, state);
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.context.input_manager.handle_cursor_moved(position);
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.context.input_manager.handle_mouse_wheel(delta);
             }
 
             _ => {},
         }
     }
 
-    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+    fn device_event(&mut self, _event_loop: &ActiveEventLoop, _device_id: DeviceId, event: DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta } = event {
+            self.context.input_manager.handle_mouse_motion(delta);
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(window) = &self.context.window else { return };
+
+        // Nothing is drawn while minimized, wait for the restoring resize instead of spinning
+        if self.context.state.as_ref().is_some_and(State::is_minimized) {
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
+
+        match self.context.frame_config.redraw_mode {
+            RedrawMode::OnDemand => event_loop.set_control_flow(ControlFlow::Wait),
+            RedrawMode::Continuous => {
+                if Instant::now() >= self.context.next_frame {
+                    window.request_redraw();
+                    event_loop.set_control_flow(ControlFlow::Poll);
+                } else {
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(self.context.next_frame));
+                }
+            }
+        }
+    }
+
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
         println!("App suspended");
     }
 
-    fn exiting(&mut self, event_loop: &ActiveEventLoop) {
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
         println!("App exiting");
+        self.game.shutdown();
     }
 }
 
@@ -162,6 +309,18 @@ pub struct AppContext<'a> {
     pub input_manager: InputManager,
 
     last_frame_time: Instant,
+    next_frame: Instant,
+    step_timer: StepTimer,
+    /// Whether input was being recorded and replayed last frame
+    input_session: (bool, bool),
+    frame_config: FrameConfig,
+    window_config: WindowConfig,
+}
+
+impl<'a> Default for AppContext<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl <'a> AppContext<'a> {
@@ -171,25 +330,45 @@ impl <'a> AppContext<'a> {
             state: None,
             input_manager: InputManager::default(),
             last_frame_time: Instant::now(),
+            next_frame: Instant::now(),
+            step_timer: StepTimer::default(),
+            input_session: (false, false),
+            frame_config: FrameConfig::default(),
+            window_config: WindowConfig::default(),
         }
     }
 
-    pub fn initalize(&mut self, event_loop: &ActiveEventLoop) {
+    /// Creates the window and GPU state, unless they already exist
+    pub fn initalize(&mut self, event_loop: &ActiveEventLoop) -> Result<(), EngineError> {
         if self.window.is_none() {
-            let window = Arc::new(event_loop.create_window(Window::default_attributes()).unwrap());
+            let window = Arc::new(event_loop.create_window(self.window_config.attributes(event_loop))?);
             self.window = Some(window.clone());
+            self.input_manager.attach_window(window.clone());
 
-            let state = pollster::block_on(State::new(window.clone()));
+            let state = pollster::block_on(State::new(window.clone(), self.frame_config.present_mode))?;
             self.state = Some(state);
+            self.apply_scale_factor(window.scale_factor());
+        }
+        Ok(())
+    }
+
+    /// Passes the window scale factor to the renderer and input, unless HiDPI scaling is off
+    fn apply_scale_factor(&mut self, scale_factor: f64) {
+        let scale_factor = if self.window_config.hidpi_scaling { scale_factor as f32 } else { 1.0 };
+
+        self.input_manager.set_scale_factor(scale_factor);
+        if let Some(state) = &mut self.state {
+            state.renderer.set_scale_factor(scale_factor);
         }
     }
 
-    pub fn draw(&mut self) {
+    /// Draws a frame, failing when the surface texture can't be acquired
+    pub fn draw<G: Game>(&mut self, game: &mut G, alpha: f32) -> Result<(), wgpu::SurfaceError> {
         if let Some(state) = &mut self.state {
-            let output = match state.surface.get_current_texture() {
-                Ok(tex) => tex,
-                Err(_) => return,
-            };
+            if state.is_minimized() {
+                return Ok(());
+            }
+            let output = state.surface.get_current_texture()?;
 
             let view = output.texture.create_view(&Default::default());
 
@@ -197,14 +376,43 @@ impl <'a> AppContext<'a> {
                 label: Some("Render Encoder"),
             });
 
-            draw_scene(&mut state.renderer, &mut encoder, &view, &self.input_manager);
+            state.renderer.begin_frame();
+            game.draw(&mut state.renderer, alpha);
+            state.renderer.end_frame(&mut encoder, &view);
 
             state.renderer.queue.submit(Some(encoder.finish()));
             output.present();
         }
+        Ok(())
     }
 
-    pub fn update(&mut self, delta_time: f32) {
+    /// Sets when the next frame is due according to `FrameConfig::target_fps`
+    fn schedule_next_frame(&mut self, frame_start: Instant) {
+        self.next_frame = match self.frame_config.target_fps {
+            Some(fps) if fps > 0.0 => (self.next_frame + Duration::from_secs_f32(1.0 / fps)).max(frame_start),
+            _ => frame_start,
+        };
+    }
+
+    /// Reads this frame's input and returns the delta time and count of game updates
+    /// to run, and the interpolation alpha. Input is read every frame, frames without
+    /// a game update carry their transitions over to the next one
+    pub fn update(&mut self, measured: f32) -> (f32, u32, f32) {
+        // Fixed steps restart from an empty accumulator when a recording or replay
+        // starts, so a replay runs the same steps as the recording
+        let input_session = (self.input_manager.is_recording(), self.input_manager.is_replaying());
+        if input_session != self.input_session {
+            self.step_timer.reset();
+            self.input_session = input_session;
+        }
+
+        let frame_time = self.input_manager.frame_delta(measured);
         self.input_manager.update();
+
+        let (step, steps, alpha) = self.step_timer.advance(frame_time);
+        if steps == 0 {
+            self.input_manager.carry_over();
+        }
+        (step, steps, alpha)
     }
 }
\ No newline at end of file
@@ -0,0 +1,78 @@
+use std::fmt;
+use winit::error::{EventLoopError, OsError};
+
+/// Failure while creating the window or setting up the GPU
+#[derive(Debug)]
+pub enum EngineError {
+    EventLoop(EventLoopError),
+    Window(OsError),
+    Surface(wgpu::CreateSurfaceError),
+    /// No adapter could render to the surface, with descriptions of the adapters that were found
+    NoAdapter { available: Vec<String> },
+    Device(wgpu::RequestDeviceError),
+    /// The surface reports no texture format the adapter can present, named by the adapter
+    NoSurfaceFormat { adapter: String },
+    /// The GPU ran out of memory while acquiring a frame
+    OutOfMemory,
+}
+
+impl EngineError {
+    /// Describes every adapter wgpu can see, for `NoAdapter`
+    pub(crate) fn no_adapter(instance: &wgpu::Instance) -> Self {
+        let available = instance
+            .enumerate_adapters(wgpu::Backends::all())
+            .iter()
+            .map(|adapter| {
+                let info = adapter.get_info();
+                format!("{} ({:?}, {:?})", info.name, info.backend, info.device_type)
+            })
+            .collect();
+
+        EngineError::NoAdapter { available }
+    }
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::EventLoop(e) => write!(f, "event loop failed: {e}"),
+            EngineError::Window(e) => write!(f, "failed to create window: {e}"),
+            EngineError::Surface(e) => write!(f, "failed to create surface: {e}"),
+            EngineError::NoAdapter { available } if available.is_empty() => {
+                write!(f, "no compatible graphics adapter found, no adapters available")
+            }
+            EngineError::NoAdapter { available } => {
+                write!(f, "no compatible graphics adapter found, available: {}", available.join(", "))
+            }
+            EngineError::Device(e) => write!(f, "failed to create graphics device: {e}"),
+            EngineError::NoSurfaceFormat { adapter } => write!(f, "window surface has no supported formats on {adapter}"),
+            EngineError::OutOfMemory => write!(f, "out of graphics memory"),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<EventLoopError> for EngineError {
+    fn from(e: EventLoopError) -> Self {
+        EngineError::EventLoop(e)
+    }
+}
+
+impl From<OsError> for EngineError {
+    fn from(e: OsError) -> Self {
+        EngineError::Window(e)
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for EngineError {
+    fn from(e: wgpu::CreateSurfaceError) -> Self {
+        EngineError::Surface(e)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for EngineError {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        EngineError::Device(e)
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use winit::keyboard::KeyCode;
+use crate::engine::events::keyboard::ButtonState::{ButtonState, InputManager, Key, MouseButton};
+use crate::engine::math::vector2::Vector2;
+
+/// Physical input that can trigger a digital action
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+}
+
+impl From<Key> for Binding {
+    fn from(key: Key) -> Self {
+        Binding::Key(key)
+    }
+}
+
+impl From<KeyCode> for Binding {
+    fn from(code: KeyCode) -> Self {
+        Binding::Key(Key::Code(code))
+    }
+}
+
+impl From<MouseButton> for Binding {
+    fn from(button: MouseButton) -> Self {
+        Binding::Mouse(button)
+    }
+}
+
+impl Binding {
+    pub fn state(&self, input: &InputManager) -> ButtonState {
+        match *self {
+            Binding::Key(key) => input.get_state(key),
+            Binding::Mouse(button) => input.get_mouse_state(button),
+        }
+    }
+
+    /// 1.0 while pressed or held, 0.0 otherwise
+    fn value(&self, input: &InputManager) -> f32 {
+        match self.state(input) {
+            ButtonState::Pressed | ButtonState::Held => 1.0,
+            ButtonState::Released | ButtonState::Up => 0.0,
+        }
+    }
+}
+
+/// Source of a one dimensional axis value
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AxisBinding {
+    /// -1.0 while `negative` is down, 1.0 while `positive` is down
+    Buttons { negative: Binding, positive: Binding },
+    /// Horizontal scroll in lines this frame
+    ScrollX,
+    /// Vertical scroll in lines this frame
+    ScrollY,
+    /// Raw horizontal mouse movement this frame
+    MotionX,
+    /// Raw vertical mouse movement this frame
+    MotionY,
+}
+
+impl AxisBinding {
+    fn value(&self, input: &InputManager) -> f32 {
+        match self {
+            AxisBinding::Buttons { negative, positive } => positive.value(input) - negative.value(input),
+            AxisBinding::ScrollX => input.scroll().x,
+            AxisBinding::ScrollY => input.scroll().y,
+            AxisBinding::MotionX => input.mouse_motion().x,
+            AxisBinding::MotionY => input.mouse_motion().y,
+        }
+    }
+}
+
+/// Four buttons combined into a 2D direction, e.g. WASD.
+/// Follows screen coordinates, so `up` gives negative `y`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Axis2DBinding {
+    pub up: Binding,
+    pub down: Binding,
+    pub left: Binding,
+    pub right: Binding,
+}
+
+impl Axis2DBinding {
+    pub fn wasd() -> Self {
+        Self {
+            up: KeyCode::KeyW.into(),
+            down: KeyCode::KeyS.into(),
+            left: KeyCode::KeyA.into(),
+            right: KeyCode::KeyD.into(),
+        }
+    }
+
+    pub fn arrows() -> Self {
+        Self {
+            up: KeyCode::ArrowUp.into(),
+            down: KeyCode::ArrowDown.into(),
+            left: KeyCode::ArrowLeft.into(),
+            right: KeyCode::ArrowRight.into(),
+        }
+    }
+
+    fn value(&self, input: &InputManager) -> Vector2 {
+        Vector2::new(
+            self.right.value(input) - self.left.value(input),
+            self.down.value(input) - self.up.value(input),
+        )
+    }
+}
+
+/// Named actions bound to one or more inputs, so gameplay code doesn't depend on concrete keys
+#[derive(Default, Clone, Debug)]
+pub struct ActionMap {
+    pub(super) buttons: HashMap<String, Vec<Binding>>,
+    pub(super) axes: HashMap<String, Vec<AxisBinding>>,
+    pub(super) axes_2d: HashMap<String, Vec<Axis2DBinding>>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds binding to a digital action, an action can have any number of bindings
+    pub fn bind(&mut self, action: &str, binding: impl Into<Binding>) -> &mut Self {
+        self.buttons.entry(action.to_string()).or_default().push(binding.into());
+        self
+    }
+
+    /// Adds binding to an axis action
+    pub fn bind_axis(&mut self, action: &str, binding: AxisBinding) -> &mut Self {
+        self.axes.entry(action.to_string()).or_default().push(binding);
+        self
+    }
+
+    /// Shorthand for `AxisBinding::Buttons`
+    pub fn bind_axis_buttons(&mut self, action: &str, negative: impl Into<Binding>, positive: impl Into<Binding>) -> &mut Self {
+        self.bind_axis(action, AxisBinding::Buttons { negative: negative.into(), positive: positive.into() })
+    }
+
+    /// Adds composite binding to a 2D axis action
+    pub fn bind_axis_2d(&mut self, action: &str, binding: Axis2DBinding) -> &mut Self {
+        self.axes_2d.entry(action.to_string()).or_default().push(binding);
+        self
+    }
+
+    /// Removes every binding of the action
+    pub fn unbind(&mut self, action: &str) {
+        self.buttons.remove(action);
+        self.axes.remove(action);
+        self.axes_2d.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.buttons.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn axis_bindings(&self, action: &str) -> &[AxisBinding] {
+        self.axes.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn axis_2d_bindings(&self, action: &str) -> &[Axis2DBinding] {
+        self.axes_2d.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn is_pressed(&self, input: &InputManager, action: &str) -> bool {
+        self.get_state(input, action) == ButtonState::Pressed
+    }
+
+    pub fn is_held(&self, input: &InputManager, action: &str) -> bool {
+        self.get_state(input, action) == ButtonState::Held
+    }
+
+    pub fn is_released(&self, input: &InputManager, action: &str) -> bool {
+        self.get_state(input, action) == ButtonState::Released
+    }
+
+    pub fn is_up(&self, input: &InputManager, action: &str) -> bool {
+        self.get_state(input, action) == ButtonState::Up
+    }
+
+    /// Combined state of all bindings. The action stays held while any binding is held,
+    /// so pressing a second key or releasing one of two doesn't retrigger it
+    pub fn get_state(&self, input: &InputManager, action: &str) -> ButtonState {
+        let states: Vec<ButtonState> = self.bindings(action).iter().map(|b| b.state(input)).collect();
+
+        if states.contains(&ButtonState::Held) {
+            ButtonState::Held
+        } else if states.contains(&ButtonState::Pressed) {
+            ButtonState::Pressed
+        } else if states.contains(&ButtonState::Released) {
+            ButtonState::Released
+        } else {
+            ButtonState::Up
+        }
+    }
+
+    /// Sum of all axis bindings. Button pairs contribute at most 1.0 together,
+    /// scroll and mouse motion are passed through unclamped
+    pub fn axis(&self, input: &InputManager, action: &str) -> f32 {
+        let (buttons, analog): (Vec<&AxisBinding>, Vec<&AxisBinding>) = self.axis_bindings(action)
+            .iter()
+            .partition(|b| matches!(b, AxisBinding::Buttons { .. }));
+
+        let digital: f32 = buttons.iter().map(|b| b.value(input)).sum();
+        let analog: f32 = analog.iter().map(|b| b.value(input)).sum();
+
+        digital.clamp(-1.0, 1.0) + analog
+    }
+
+    /// Direction of a 2D axis action, normalized so diagonals aren't faster
+    pub fn axis_2d(&self, input: &InputManager, action: &str) -> Vector2 {
+        let value = self.axis_2d_bindings(action)
+            .iter()
+            .fold(Vector2::zero(), |sum, b| sum + b.value(input));
+
+        if value.length() > 1.0 {
+            value.normalize()
+        } else {
+            value
+        }
+    }
+}
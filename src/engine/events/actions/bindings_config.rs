@@ -0,0 +1,424 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use serde::de::IntoDeserializer;
+use winit::keyboard::KeyCode;
+use crate::engine::events::actions::action_map::{ActionMap, Axis2DBinding, AxisBinding, Binding};
+use crate::engine::events::keyboard::ButtonState::{Key, MouseButton};
+
+/// On-disk layout of an `ActionMap`:
+///
+/// ```toml
+/// [buttons]
+/// jump = ["Space", "MouseLeft"]
+///
+/// [axes]
+/// zoom = [{ negative = "KeyQ", positive = "KeyE" }, "ScrollY"]
+///
+/// [axes_2d]
+/// move = [{ up = "KeyW", down = "KeyS", left = "KeyA", right = "KeyD" }]
+/// ```
+///
+/// Keys use winit `KeyCode` names, mouse buttons are `MouseLeft`, `MouseRight`,
+/// `MouseMiddle`, `MouseBack`, `MouseForward` or `Mouse<n>`
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct BindingsFile {
+    buttons: BTreeMap<String, Vec<String>>,
+    axes: BTreeMap<String, Vec<AxisEntry>>,
+    axes_2d: BTreeMap<String, Vec<Axis2DEntry>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum AxisEntry {
+    Buttons { negative: String, positive: String },
+    Analog(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Axis2DEntry {
+    up: String,
+    down: String,
+    left: String,
+    right: String,
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    /// Names that don't match any key, mouse button or axis, as `(action, name)`
+    UnknownNames(Vec<(String, String)>),
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingsError::Io(e) => write!(f, "failed to access bindings file: {e}"),
+            BindingsError::Parse(e) => write!(f, "failed to parse bindings: {e}"),
+            BindingsError::Serialize(e) => write!(f, "failed to serialize bindings: {e}"),
+            BindingsError::UnknownNames(names) => {
+                let names: Vec<String> = names
+                    .iter()
+                    .map(|(action, name)| format!("`{name}` in `{action}`"))
+                    .collect();
+                write!(f, "unknown input names: {}", names.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {}
+
+impl From<std::io::Error> for BindingsError {
+    fn from(e: std::io::Error) -> Self {
+        BindingsError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for BindingsError {
+    fn from(e: toml::de::Error) -> Self {
+        BindingsError::Parse(e)
+    }
+}
+
+impl From<toml::ser::Error> for BindingsError {
+    fn from(e: toml::ser::Error) -> Self {
+        BindingsError::Serialize(e)
+    }
+}
+
+/// Input bound to more than one action
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BindingConflict {
+    pub binding: Binding,
+    pub actions: Vec<String>,
+}
+
+impl ActionMap {
+    /// Parses bindings, failing with every unknown name at once
+    pub fn from_toml(source: &str) -> Result<Self, BindingsError> {
+        let file: BindingsFile = toml::from_str(source)?;
+        let mut map = ActionMap::new();
+        let mut unknown = Vec::new();
+
+        for (action, names) in &file.buttons {
+            for name in names {
+                if let Some(binding) = resolve(&mut unknown, action, name) {
+                    map.bind(action, binding);
+                }
+            }
+        }
+
+        for (action, entries) in &file.axes {
+            for entry in entries {
+                let binding = match entry {
+                    AxisEntry::Buttons { negative, positive } => match (resolve(&mut unknown, action, negative), resolve(&mut unknown, action, positive)) {
+                        (Some(negative), Some(positive)) => Some(AxisBinding::Buttons { negative, positive }),
+                        _ => None,
+                    },
+                    AxisEntry::Analog(name) => {
+                        let binding = match name.as_str() {
+                            "ScrollX" => Some(AxisBinding::ScrollX),
+                            "ScrollY" => Some(AxisBinding::ScrollY),
+                            "MotionX" => Some(AxisBinding::MotionX),
+                            "MotionY" => Some(AxisBinding::MotionY),
+                            _ => None,
+                        };
+                        if binding.is_none() {
+                            unknown.push((action.clone(), name.clone()));
+                        }
+                        binding
+                    }
+                };
+
+                if let Some(binding) = binding {
+                    map.bind_axis(action, binding);
+                }
+            }
+        }
+
+        for (action, entries) in &file.axes_2d {
+            for entry in entries {
+                let up = resolve(&mut unknown, action, &entry.up);
+                let down = resolve(&mut unknown, action, &entry.down);
+                let left = resolve(&mut unknown, action, &entry.left);
+                let right = resolve(&mut unknown, action, &entry.right);
+
+                if let (Some(up), Some(down), Some(left), Some(right)) = (up, down, left, right) {
+                    map.bind_axis_2d(action, Axis2DBinding { up, down, left, right });
+                }
+            }
+        }
+
+        if !unknown.is_empty() {
+            return Err(BindingsError::UnknownNames(unknown));
+        }
+
+        Ok(map)
+    }
+
+    /// Serializes bindings to TOML. Keys winit couldn't identify have no name and are skipped
+    pub fn to_toml(&self) -> Result<String, BindingsError> {
+        let mut file = BindingsFile::default();
+
+        for (action, bindings) in &self.buttons {
+            file.buttons.insert(action.clone(), bindings.iter().filter_map(binding_name).collect());
+        }
+
+        for (action, bindings) in &self.axes {
+            let entries = bindings.iter().filter_map(|binding| match binding {
+                AxisBinding::Buttons { negative, positive } => Some(AxisEntry::Buttons {
+                    negative: binding_name(negative)?,
+                    positive: binding_name(positive)?,
+                }),
+                AxisBinding::ScrollX => Some(AxisEntry::Analog("ScrollX".to_string())),
+                AxisBinding::ScrollY => Some(AxisEntry::Analog("ScrollY".to_string())),
+                AxisBinding::MotionX => Some(AxisEntry::Analog("MotionX".to_string())),
+                AxisBinding::MotionY => Some(AxisEntry::Analog("MotionY".to_string())),
+            });
+            file.axes.insert(action.clone(), entries.collect());
+        }
+
+        for (action, bindings) in &self.axes_2d {
+            let entries = bindings.iter().filter_map(|binding| Some(Axis2DEntry {
+                up: binding_name(&binding.up)?,
+                down: binding_name(&binding.down)?,
+                left: binding_name(&binding.left)?,
+                right: binding_name(&binding.right)?,
+            }));
+            file.axes_2d.insert(action.clone(), entries.collect());
+        }
+
+        Ok(toml::to_string_pretty(&file)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BindingsError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BindingsError> {
+        std::fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    /// Loads bindings from `path`, falling back to `defaults` when the file is missing,
+    /// unreadable or invalid. Problems other than a missing file are printed
+    pub fn load_or(path: impl AsRef<Path>, defaults: &ActionMap) -> Self {
+        match Self::load(&path) {
+            Ok(map) => map,
+            Err(BindingsError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => defaults.clone(),
+            Err(e) => {
+                eprintln!("{}: {e}, using default bindings", path.as_ref().display());
+                defaults.clone()
+            }
+        }
+    }
+
+    /// Inputs used by more than one action, sorted by their action names and then by input name
+    pub fn conflicts(&self) -> Vec<BindingConflict> {
+        let mut users: HashMap<Binding, Vec<String>> = HashMap::new();
+        let mut add = |binding: Binding, action: &String| {
+            let actions = users.entry(binding).or_default();
+            if !actions.contains(action) {
+                actions.push(action.clone());
+            }
+        };
+
+        for (action, bindings) in &self.buttons {
+            bindings.iter().for_each(|&b| add(b, action));
+        }
+        for (action, bindings) in &self.axes {
+            for binding in bindings {
+                if let AxisBinding::Buttons { negative, positive } = *binding {
+                    add(negative, action);
+                    add(positive, action);
+                }
+            }
+        }
+        for (action, bindings) in &self.axes_2d {
+            for b in bindings {
+                [b.up, b.down, b.left, b.right].into_iter().for_each(|binding| add(binding, action));
+            }
+        }
+
+        let mut conflicts: Vec<BindingConflict> = users
+            .into_iter()
+            .filter(|(_, actions)| actions.len() > 1)
+            .map(|(binding, mut actions)| {
+                actions.sort();
+                BindingConflict { binding, actions }
+            })
+            .collect();
+        conflicts.sort_by(|a, b| {
+            a.actions.cmp(&b.actions).then_with(|| binding_name(&a.binding).cmp(&binding_name(&b.binding)))
+        });
+
+        conflicts
+    }
+}
+
+/// Parses `name`, remembering it in `unknown` when it isn't a valid key or mouse button
+fn resolve(unknown: &mut Vec<(String, String)>, action: &str, name: &str) -> Option<Binding> {
+    let binding = parse_binding(name);
+    if binding.is_none() {
+        unknown.push((action.to_string(), name.to_string()));
+    }
+    binding
+}
+
+fn parse_binding(name: &str) -> Option<Binding> {
+    if let Some(button) = name.strip_prefix("Mouse") {
+        let button = match button {
+            "Left" => MouseButton::Left,
+            "Right" => MouseButton::Right,
+            "Middle" => MouseButton::Middle,
+            "Back" => MouseButton::Back,
+            "Forward" => MouseButton::Forward,
+            other => MouseButton::Other(other.parse().ok()?),
+        };
+        return Some(Binding::Mouse(button));
+    }
+
+    let deserializer: serde::de::value::StrDeserializer<serde::de::value::Error> = name.into_deserializer();
+    KeyCode::deserialize(deserializer).ok().map(|code| Binding::Key(Key::Code(code)))
+}
+
+fn binding_name(binding: &Binding) -> Option<String> {
+    match binding {
+        Binding::Key(Key::Code(code)) => Some(format!("{code:?}")),
+        Binding::Key(Key::Unidentified(_)) => None,
+        Binding::Mouse(MouseButton::Other(n)) => Some(format!("Mouse{n}")),
+        Binding::Mouse(button) => Some(format!("Mouse{button:?}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> Binding {
+        Binding::Key(Key::Code(code))
+    }
+
+    fn sample() -> ActionMap {
+        let mut map = ActionMap::new();
+        map.bind("jump", KeyCode::Space)
+            .bind("jump", MouseButton::Left)
+            .bind("dash", MouseButton::Other(7))
+            .bind_axis_buttons("zoom", KeyCode::KeyQ, KeyCode::KeyE)
+            .bind_axis("zoom", AxisBinding::ScrollY)
+            .bind_axis_2d("move", Axis2DBinding::wasd());
+        map
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let map = sample();
+        let source = map.to_toml().unwrap();
+        let parsed = ActionMap::from_toml(&source).unwrap();
+
+        assert_eq!(parsed.bindings("jump"), map.bindings("jump"));
+        assert_eq!(parsed.bindings("dash"), map.bindings("dash"));
+        assert_eq!(parsed.axis_bindings("zoom"), map.axis_bindings("zoom"));
+        assert_eq!(parsed.axis_2d_bindings("move"), map.axis_2d_bindings("move"));
+        assert_eq!(parsed.to_toml().unwrap(), source);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("yugin-bindings-{}.toml", std::process::id()));
+        sample().save(&path).unwrap();
+        let loaded = ActionMap::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap().bindings("jump"), sample().bindings("jump"));
+    }
+
+    #[test]
+    fn unknown_names_are_collected() {
+        let source = r#"
+            [buttons]
+            jump = ["Space", "Spacebar"]
+
+            [axes]
+            zoom = [{ negative = "KeyQ", positive = "KeyZZ" }, "ScrollZ"]
+
+            [axes_2d]
+            move = [{ up = "KeyW", down = "KeyS", left = "Left", right = "KeyD" }]
+        "#;
+
+        let Err(BindingsError::UnknownNames(names)) = ActionMap::from_toml(source) else {
+            panic!("expected unknown names");
+        };
+        let names: Vec<(&str, &str)> = names.iter().map(|(a, n)| (a.as_str(), n.as_str())).collect();
+        assert_eq!(names, [("jump", "Spacebar"), ("zoom", "KeyZZ"), ("zoom", "ScrollZ"), ("move", "Left")]);
+    }
+
+    #[test]
+    fn mouse_names() {
+        assert_eq!(parse_binding("MouseLeft"), Some(Binding::Mouse(MouseButton::Left)));
+        assert_eq!(parse_binding("MouseForward"), Some(Binding::Mouse(MouseButton::Forward)));
+        assert_eq!(parse_binding("Mouse5"), Some(Binding::Mouse(MouseButton::Other(5))));
+        assert_eq!(parse_binding("Mouse"), None);
+        assert_eq!(parse_binding("Mouse-1"), None);
+        assert_eq!(parse_binding("MouseUp"), None);
+        assert_eq!(binding_name(&Binding::Mouse(MouseButton::Other(5))).as_deref(), Some("Mouse5"));
+        assert_eq!(binding_name(&Binding::Mouse(MouseButton::Back)).as_deref(), Some("MouseBack"));
+    }
+
+    #[test]
+    fn conflicts_are_sorted_by_actions() {
+        let mut map = ActionMap::new();
+        map.bind("shoot", KeyCode::Space)
+            .bind("jump", KeyCode::Space)
+            .bind("use", KeyCode::KeyE)
+            .bind_axis_buttons("zoom", KeyCode::KeyQ, KeyCode::KeyE)
+            .bind("crouch", KeyCode::KeyC)
+            .bind_axis_2d("move", Axis2DBinding::wasd())
+            .bind("back", KeyCode::KeyS);
+
+        let conflicts = map.conflicts();
+        let actions: Vec<&[String]> = conflicts.iter().map(|c| c.actions.as_slice()).collect();
+        assert_eq!(actions, [vec!["back", "move"], vec!["jump", "shoot"], vec!["use", "zoom"]]);
+        assert_eq!(conflicts[0].binding, key(KeyCode::KeyS));
+        assert_eq!(conflicts[1].binding, key(KeyCode::Space));
+        assert_eq!(conflicts[2].binding, key(KeyCode::KeyE));
+
+        // Inputs shared by the same actions are ordered by name, not by hash map order
+        for _ in 0..20 {
+            let mut map = ActionMap::new();
+            map.bind("jump", KeyCode::Space)
+                .bind("jump", KeyCode::KeyJ)
+                .bind("jump", MouseButton::Left)
+                .bind("shoot", KeyCode::KeyJ)
+                .bind("shoot", MouseButton::Left)
+                .bind("shoot", KeyCode::Space);
+
+            let bindings: Vec<Binding> = map.conflicts().into_iter().map(|c| c.binding).collect();
+            assert_eq!(bindings, [key(KeyCode::KeyJ), Binding::Mouse(MouseButton::Left), key(KeyCode::Space)]);
+        }
+    }
+
+    #[test]
+    fn load_or_falls_back_to_defaults() {
+        let defaults = sample();
+        let missing = std::env::temp_dir().join("yugin-bindings-missing.toml");
+        assert_eq!(ActionMap::load_or(&missing, &defaults).bindings("jump"), defaults.bindings("jump"));
+
+        let invalid = std::env::temp_dir().join(format!("yugin-bindings-invalid-{}.toml", std::process::id()));
+        std::fs::write(&invalid, "[buttons]\njump = [\"Nope\"]\n").unwrap();
+        let loaded = ActionMap::load_or(&invalid, &defaults);
+        std::fs::remove_file(&invalid).unwrap();
+        assert_eq!(loaded.bindings("jump"), defaults.bindings("jump"));
+
+        let valid = std::env::temp_dir().join(format!("yugin-bindings-valid-{}.toml", std::process::id()));
+        std::fs::write(&valid, "[buttons]\njump = [\"KeyJ\"]\n").unwrap();
+        let loaded = ActionMap::load_or(&valid, &defaults);
+        std::fs::remove_file(&valid).unwrap();
+        assert_eq!(loaded.bindings("jump"), [key(KeyCode::KeyJ)]);
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use winit::keyboard::KeyCode;
+use crate::engine::events::actions::action_map::Binding;
+use crate::engine::events::keyboard::ButtonState::{ButtonState, InputManager, Key, MouseButton};
+
+/// Keys held together, e.g. Ctrl+Shift+S. Each part of the chord can be
+/// satisfied by any of several bindings, so `ctrl()` accepts both Control keys
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Chord {
+    parts: Vec<Vec<Binding>>,
+    exclusive: bool,
+    window: Option<f32>,
+}
+
+impl Chord {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(self, binding: impl Into<Binding>) -> Self {
+        self.with_any([binding.into()])
+    }
+
+    /// Adds a part satisfied by any of `bindings`
+    pub fn with_any(mut self, bindings: impl IntoIterator<Item = Binding>) -> Self {
+        self.parts.push(bindings.into_iter().collect());
+        self
+    }
+
+    pub fn ctrl(self) -> Self {
+        self.with_any([KeyCode::ControlLeft.into(), KeyCode::ControlRight.into()])
+    }
+
+    pub fn shift(self) -> Self {
+        self.with_any([KeyCode::ShiftLeft.into(), KeyCode::ShiftRight.into()])
+    }
+
+    pub fn alt(self) -> Self {
+        self.with_any([KeyCode::AltLeft.into(), KeyCode::AltRight.into()])
+    }
+
+    pub fn super_key(self) -> Self {
+        self.with_any([KeyCode::SuperLeft.into(), KeyCode::SuperRight.into()])
+    }
+
+    /// Only match while no other key or mouse button is down,
+    /// so Ctrl+S doesn't also fire for Ctrl+Shift+S
+    pub fn exclusive(mut self) -> Self {
+        self.exclusive = true;
+        self
+    }
+
+    /// Requires every part to be pressed within `seconds` of each other,
+    /// e.g. two attack buttons hit together
+    pub fn within(mut self, seconds: f32) -> Self {
+        self.window = Some(seconds);
+        self
+    }
+
+    fn bindings(&self) -> impl Iterator<Item = &Binding> {
+        self.parts.iter().flatten()
+    }
+
+    /// All parts are down and, for exclusive chords, nothing else is
+    fn is_down(&self, input: &InputManager, press_times: &HashMap<Binding, f32>) -> bool {
+        if self.parts.is_empty() {
+            return false;
+        }
+
+        let mut first_press = f32::INFINITY;
+        let mut last_press = f32::NEG_INFINITY;
+        for part in &self.parts {
+            let Some(down) = part.iter().find(|b| is_down(b.state(input))) else {
+                return false;
+            };
+            let time = press_times.get(down).copied().unwrap_or(0.0);
+            first_press = first_press.min(time);
+            last_press = last_press.max(time);
+        }
+
+        if self.window.is_some_and(|window| last_press - first_press > window) {
+            return false;
+        }
+
+        if self.exclusive {
+            let other_key = input.down_keys().any(|key| !self.contains(Binding::Key(key)));
+            let other_button = input.down_mouse_buttons().any(|button| !self.contains(Binding::Mouse(button)));
+            if other_key || other_button {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn contains(&self, binding: Binding) -> bool {
+        self.bindings().any(|&b| b == binding)
+    }
+}
+
+impl From<Binding> for Chord {
+    fn from(binding: Binding) -> Self {
+        Chord::new().with(binding)
+    }
+}
+
+impl From<Key> for Chord {
+    fn from(key: Key) -> Self {
+        Chord::new().with(key)
+    }
+}
+
+impl From<KeyCode> for Chord {
+    fn from(code: KeyCode) -> Self {
+        Chord::new().with(code)
+    }
+}
+
+impl From<MouseButton> for Chord {
+    fn from(button: MouseButton) -> Self {
+        Chord::new().with(button)
+    }
+}
+
+/// Chords entered one after another within a time window,
+/// e.g. down, down-forward, forward + punch
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sequence {
+    steps: Vec<Chord>,
+    window: f32,
+}
+
+impl Sequence {
+    /// `window` is the most seconds allowed between the first and the last step
+    pub fn new(window: f32) -> Self {
+        Self { steps: Vec::new(), window }
+    }
+
+    pub fn then(mut self, step: impl Into<Chord>) -> Self {
+        self.steps.push(step.into());
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ComboKind {
+    Chord,
+    Sequence,
+}
+
+/// Chord or sequence matched during the last `ComboDetector::update`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComboEvent {
+    pub name: String,
+    pub kind: ComboKind,
+    /// Detector time of the match, in seconds
+    pub time: f32,
+}
+
+struct ChordEntry {
+    name: String,
+    chord: Chord,
+    down: bool,
+}
+
+struct SequenceEntry {
+    name: String,
+    sequence: Sequence,
+    /// Whether each step was down last frame
+    steps_down: Vec<bool>,
+    /// In-progress attempts as (next step, start time)
+    attempts: Vec<(usize, f32)>,
+}
+
+/// Detects named chords and sequences from `InputManager`'s per-frame transitions.
+/// Call `update` once per frame after the input manager was updated
+#[derive(Default)]
+pub struct ComboDetector {
+    chords: Vec<ChordEntry>,
+    sequences: Vec<SequenceEntry>,
+    time: f32,
+    press_times: HashMap<Binding, f32>,
+    events: Vec<ComboEvent>,
+}
+
+impl ComboDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fires when the last missing part of the chord is pressed. Releasing a key
+    /// that blocked an exclusive chord doesn't trigger it
+    pub fn add_chord(&mut self, name: &str, chord: Chord) -> &mut Self {
+        self.chords.push(ChordEntry { name: name.to_string(), chord, down: false });
+        self
+    }
+
+    /// Steps advance when their chord becomes down, including by releasing keys,
+    /// so an exclusive `Right` step follows `Down + Right` once Down is let go.
+    /// Unrelated input in between doesn't break the sequence
+    pub fn add_sequence(&mut self, name: &str, sequence: Sequence) -> &mut Self {
+        let steps_down = vec![false; sequence.steps.len()];
+        self.sequences.push(SequenceEntry { name: name.to_string(), sequence, steps_down, attempts: Vec::new() });
+        self
+    }
+
+    /// Removes chords and sequences with this name
+    pub fn remove(&mut self, name: &str) {
+        self.chords.retain(|entry| entry.name != name);
+        self.sequences.retain(|entry| entry.name != name);
+    }
+
+    pub fn update(&mut self, input: &InputManager, delta_time: f32) {
+        self.time += delta_time;
+        self.events.clear();
+
+        for key in input.down_keys().filter(|&key| input.is_pressed(key)) {
+            self.press_times.insert(Binding::Key(key), self.time);
+        }
+        for button in input.down_mouse_buttons().filter(|&button| input.is_mouse_pressed(button)) {
+            self.press_times.insert(Binding::Mouse(button), self.time);
+        }
+
+        for entry in &mut self.chords {
+            let down = entry.chord.is_down(input, &self.press_times);
+            let pressed = entry.chord.bindings().any(|b| b.state(input) == ButtonState::Pressed);
+
+            if down && !entry.down && pressed {
+                self.events.push(ComboEvent { name: entry.name.clone(), kind: ComboKind::Chord, time: self.time });
+            }
+            entry.down = down;
+        }
+
+        for entry in &mut self.sequences {
+            let steps = &entry.sequence.steps;
+            let entered: Vec<bool> = steps
+                .iter()
+                .zip(&mut entry.steps_down)
+                .map(|(step, was_down)| {
+                    let down = step.is_down(input, &self.press_times);
+                    let entered = down && !*was_down;
+                    *was_down = down;
+                    entered
+                })
+                .collect();
+
+            let time = self.time;
+            let window = entry.sequence.window;
+            entry.attempts.retain(|&(_, start)| time - start <= window);
+
+            for (next, _) in &mut entry.attempts {
+                if entered[*next] {
+                    *next += 1;
+                }
+            }
+            if entered.first() == Some(&true) {
+                entry.attempts.push((1, time));
+            }
+
+            if entry.attempts.iter().any(|&(next, _)| next == steps.len()) {
+                entry.attempts.clear();
+                self.events.push(ComboEvent { name: entry.name.clone(), kind: ComboKind::Sequence, time });
+            }
+        }
+    }
+
+    /// Chords and sequences matched this frame, in registration order
+    pub fn events(&self) -> &[ComboEvent] {
+        &self.events
+    }
+
+    pub fn is_triggered(&self, name: &str) -> bool {
+        self.events.iter().any(|event| event.name == name)
+    }
+}
+
+fn is_down(state: ButtonState) -> bool {
+    matches!(state, ButtonState::Pressed | ButtonState::Held)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::ElementState;
+    use winit::event::ElementState::{Pressed, Released};
+
+    struct Harness {
+        input: InputManager,
+        detector: ComboDetector,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            Self { input: InputManager::default(), detector: ComboDetector::new() }
+        }
+
+        /// Runs one frame of `delta_time` seconds after applying key events
+        fn frame(&mut self, delta_time: f32, events: &[(KeyCode, ElementState)]) -> Vec<String> {
+            for &(code, state) in events {
+                self.input.handle_key(Key::Code(code), state);
+            }
+            self.input.update();
+            self.detector.update(&self.input, delta_time);
+            self.detector.events().iter().map(|event| event.name.clone()).collect()
+        }
+
+        /// Presses and releases `code` over two frames, returning events of both
+        fn tap(&mut self, delta_time: f32, code: KeyCode) -> Vec<String> {
+            let mut events = self.frame(delta_time, &[(code, Pressed)]);
+            events.extend(self.frame(0.0, &[(code, Released)]));
+            events
+        }
+    }
+
+    #[test]
+    fn sequence_within_window_matches() {
+        let mut h = Harness::new();
+        h.detector.add_sequence("hadouken", Sequence::new(0.5).then(KeyCode::KeyS).then(KeyCode::KeyD));
+
+        assert!(h.tap(0.1, KeyCode::KeyS).is_empty());
+        assert_eq!(h.tap(0.3, KeyCode::KeyD), ["hadouken"]);
+    }
+
+    #[test]
+    fn sequence_times_out() {
+        let mut h = Harness::new();
+        h.detector.add_sequence("hadouken", Sequence::new(0.5).then(KeyCode::KeyS).then(KeyCode::KeyD));
+
+        assert!(h.tap(0.1, KeyCode::KeyS).is_empty());
+        assert!(h.tap(0.6, KeyCode::KeyD).is_empty());
+
+        // A fresh attempt still works after the old one expired
+        assert!(h.tap(0.1, KeyCode::KeyS).is_empty());
+        assert_eq!(h.tap(0.1, KeyCode::KeyD), ["hadouken"]);
+    }
+
+    #[test]
+    fn overlapping_attempts_match_from_a_later_start() {
+        let mut h = Harness::new();
+        let sequence = Sequence::new(0.5).then(KeyCode::KeyS).then(KeyCode::KeyS).then(KeyCode::KeyD);
+        h.detector.add_sequence("uppercut", sequence);
+
+        // The first down is too early, but the second starts a new attempt
+        assert!(h.tap(0.0, KeyCode::KeyS).is_empty());
+        assert!(h.tap(0.4, KeyCode::KeyS).is_empty());
+        assert!(h.tap(0.2, KeyCode::KeyS).is_empty());
+        assert_eq!(h.tap(0.2, KeyCode::KeyD), ["uppercut"]);
+
+        // Matching clears every attempt, a lone forward doesn't match again
+        assert!(h.tap(0.1, KeyCode::KeyD).is_empty());
+    }
+
+    #[test]
+    fn exclusive_chord_is_blocked_by_other_keys() {
+        let mut h = Harness::new();
+        h.detector.add_chord("save", Chord::new().ctrl().with(KeyCode::KeyS).exclusive());
+        h.detector.add_chord("save_any", Chord::new().ctrl().with(KeyCode::KeyS));
+
+        let events = h.frame(0.1, &[(KeyCode::ControlLeft, Pressed), (KeyCode::ShiftLeft, Pressed), (KeyCode::KeyS, Pressed)]);
+        assert_eq!(events, ["save_any"]);
+
+        // Releasing the blocking key doesn't trigger the exclusive chord
+        assert!(h.frame(0.1, &[(KeyCode::ShiftLeft, Released)]).is_empty());
+
+        h.frame(0.1, &[(KeyCode::KeyS, Released)]);
+        assert_eq!(h.frame(0.1, &[(KeyCode::KeyS, Pressed)]), ["save", "save_any"]);
+    }
+
+    #[test]
+    fn exclusive_step_waits_for_other_keys_to_be_released() {
+        let mut h = Harness::new();
+        let forward = Chord::new().with(KeyCode::KeyD).exclusive();
+        h.detector.add_sequence("dash", Sequence::new(0.5).then(KeyCode::KeyS).then(forward));
+
+        h.frame(0.1, &[(KeyCode::KeyS, Pressed)]);
+        assert!(h.frame(0.1, &[(KeyCode::KeyD, Pressed)]).is_empty());
+        assert_eq!(h.frame(0.1, &[(KeyCode::KeyS, Released)]), ["dash"]);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod action_map;
+pub mod bindings_config;
+pub mod combos;
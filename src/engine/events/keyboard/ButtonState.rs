@@ -1,6 +1,14 @@
 use std::collections::HashMap;
-use winit::event::ElementState;
-use winit::keyboard::{KeyCode, PhysicalKey};
+use std::hash::Hash;
+use std::sync::Arc;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, Ime, MouseScrollDelta};
+use winit::keyboard::{Key as LogicalKey, PhysicalKey};
+use winit::window::{CursorGrabMode, Window};
+use crate::engine::events::keyboard::text_input::{Preedit, TextEvent};
+use crate::engine::events::recording::input_recording::{InputEvent, InputRecording, Recorder, Replay};
+use crate::engine::math::vector2::Vector2;
+use crate::engine::renderer::camera::Camera2D;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ButtonState {
@@ -11,47 +19,352 @@ pub enum ButtonState {
 }
 
 pub type Key = PhysicalKey;
+pub type MouseButton = winit::event::MouseButton;
 
-#[derive(Default)]
-pub struct InputManager {
-    key_states: HashMap<Key, ButtonState>,
-    just_released: Vec<Key>,
-    just_pressed: Vec<Key>,
+/// Pixel scroll (touchpads) is converted to lines using this ratio
+const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+
+/// Pressed -> Held -> Released -> Up lifecycle shared by keys and mouse buttons
+struct ButtonTracker<T> {
+    states: HashMap<T, ButtonState>,
+    just_released: Vec<T>,
+    just_pressed: Vec<T>,
+    /// Releases of buttons pressed since the last update, applied one update later
+    /// so a click shorter than a frame still reports Pressed and then Released
+    deferred_releases: Vec<T>,
 }
 
-impl InputManager {
-    pub fn update(&mut self) {
-        for (_, state) in self.key_states.iter_mut() {
+impl<T> Default for ButtonTracker<T> {
+    fn default() -> Self {
+        Self {
+            states: HashMap::new(),
+            just_released: Vec::new(),
+            just_pressed: Vec::new(),
+            deferred_releases: Vec::new(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash> ButtonTracker<T> {
+    /// Applies events since the last update. With `carry` the previous transitions
+    /// weren't seen yet, so they are kept and releases of just pressed buttons wait
+    fn update(&mut self, carry: bool) {
+        if !carry {
+            self.settle();
+        }
+
+        for button in std::mem::take(&mut self.just_released) {
+            if carry && self.get(button) == ButtonState::Pressed {
+                self.deferred_releases.push(button);
+            } else {
+                self.states.insert(button, ButtonState::Released);
+            }
+        }
+
+        for button in self.just_pressed.drain(..) {
+            self.states.insert(button, ButtonState::Pressed);
+        }
+
+        self.just_released.append(&mut self.deferred_releases);
+    }
+
+    /// Ends the transitions of the last update, Pressed becomes Held and Released becomes Up
+    fn settle(&mut self) {
+        for (_, state) in self.states.iter_mut() {
             *state = match *state {
                 ButtonState::Pressed => ButtonState::Held,
                 ButtonState::Released => ButtonState::Up,
                 other => other,
             };
         }
+    }
 
-        for key in self.just_released.drain(..) {
-            self.key_states.insert(key, ButtonState::Released);
-        }
-
-
-        for key in self.just_pressed.drain(..) {
-            self.key_states.insert(key, ButtonState::Pressed);
-        }
+    /// Forgets every state and pending transition, then marks `held` as held
+    fn reset(&mut self, held: &[T]) {
+        self.states.clear();
+        self.just_released.clear();
+        self.just_pressed.clear();
+        self.deferred_releases.clear();
+        self.states.extend(held.iter().map(|&button| (button, ButtonState::Held)));
     }
 
-    pub fn handle_key(&mut self, key: Key, state: ElementState) {
+    fn handle(&mut self, button: T, state: ElementState) {
         match state {
             ElementState::Pressed => {
-                match self.key_states.get(&key) {
+                // Released and pressed again within one frame, the button simply stays down
+                if let Some(i) = self.just_released.iter().position(|&b| b == button) {
+                    self.just_released.remove(i);
+                    return;
+                }
+                self.deferred_releases.retain(|&b| b != button);
+
+                match self.states.get(&button) {
                     Some(ButtonState::Held | ButtonState::Pressed) => {},
-                    _ => self.just_pressed.push(key),
+                    _ if self.just_pressed.contains(&button) => {},
+                    _ => self.just_pressed.push(button),
                 }
             }
             ElementState::Released => {
-                self.just_released.push(key);
-                // self.key_states.insert(key, ButtonState::Released);
+                if self.just_pressed.contains(&button) {
+                    if !self.deferred_releases.contains(&button) {
+                        self.deferred_releases.push(button);
+                    }
+                } else if !self.just_released.contains(&button) {
+                    self.just_released.push(button);
+                }
+            }
+        }
+    }
+
+    fn get(&self, button: T) -> ButtonState {
+        self.states.get(&button).copied().unwrap_or(ButtonState::Up)
+    }
+
+    fn down(&self) -> impl Iterator<Item = T> + '_ {
+        self.states
+            .iter()
+            .filter(|(_, state)| matches!(state, ButtonState::Pressed | ButtonState::Held))
+            .map(|(&button, _)| button)
+    }
+}
+
+/// Physical pixels per logical pixel, 1.0 until the app reports the window's
+#[derive(Copy, Clone)]
+struct ScaleFactor(f32);
+
+impl Default for ScaleFactor {
+    fn default() -> Self {
+        ScaleFactor(1.0)
+    }
+}
+
+#[derive(Default)]
+pub struct InputManager {
+    keys: ButtonTracker<Key>,
+    mouse_buttons: ButtonTracker<MouseButton>,
+
+    cursor_position: Vector2,
+    previous_cursor_position: Vector2,
+    cursor_delta: Vector2,
+
+    pending_motion: Vector2,
+    mouse_motion: Vector2,
+
+    pending_scroll: Vector2,
+    scroll: Vector2,
+
+    pending_text: Vec<TextEvent>,
+    text_events: Vec<TextEvent>,
+    preedit: Option<Preedit>,
+
+    window: Option<Arc<Window>>,
+    scale_factor: ScaleFactor,
+
+    frame: u64,
+    /// The last update wasn't followed by a game update, so its transitions carry over
+    carry: bool,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+}
+
+impl InputManager {
+    pub fn update(&mut self) {
+        if self.replay.as_ref().is_some_and(|replay| replay.is_finished(self.frame)) {
+            self.stop_replay();
+        }
+
+        if let Some(replay) = &mut self.replay {
+            for event in replay.events_for(self.frame) {
+                self.apply(event);
             }
         }
+
+        let carry = std::mem::take(&mut self.carry);
+        self.keys.update(carry);
+        self.mouse_buttons.update(carry);
+
+        let cursor_delta = self.cursor_position - self.previous_cursor_position;
+        self.previous_cursor_position = self.cursor_position;
+        let mouse_motion = std::mem::take(&mut self.pending_motion);
+        let scroll = std::mem::take(&mut self.pending_scroll);
+        let text_events = std::mem::take(&mut self.pending_text);
+        if carry {
+            self.cursor_delta = self.cursor_delta + cursor_delta;
+            self.mouse_motion = self.mouse_motion + mouse_motion;
+            self.scroll = self.scroll + scroll;
+            self.text_events.extend(text_events);
+        } else {
+            self.cursor_delta = cursor_delta;
+            self.mouse_motion = mouse_motion;
+            self.scroll = scroll;
+            self.text_events = text_events;
+        }
+
+        self.frame += 1;
+    }
+
+    /// Keeps this frame's transitions and deltas for the next `update`,
+    /// called when a fixed step frame ran no game update
+    pub(crate) fn carry_over(&mut self) {
+        self.carry = true;
+    }
+
+    /// Clears the transitions and deltas already seen by a game update,
+    /// so further fixed steps in the same frame don't see them again
+    pub(crate) fn settle(&mut self) {
+        self.keys.settle();
+        self.mouse_buttons.settle();
+        self.cursor_delta = Vector2::zero();
+        self.mouse_motion = Vector2::zero();
+        self.scroll = Vector2::zero();
+        self.text_events.clear();
+    }
+
+    /// Number of `update` calls so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Returns frame time to use for the coming frame: the recorded one while replaying,
+    /// otherwise `measured`, which is stored when recording. Called once per frame by `App`
+    pub(crate) fn frame_delta(&mut self, measured: f32) -> f32 {
+        if let Some(replay) = &self.replay {
+            let index = (self.frame - replay.start_frame) as usize;
+            return replay.recording.frame_times.get(index).copied().unwrap_or(measured);
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.recording.frame_times.push(measured);
+        }
+        measured
+    }
+
+    /// Starts capturing every input event, replacing a recording in progress.
+    /// Buttons down at this point are saved so the replay starts from the same state
+    pub fn start_recording(&mut self) {
+        let held_keys = self.keys.down().collect();
+        let held_mouse_buttons = self.mouse_buttons.down().collect();
+        self.recorder = Some(Recorder::new(self.frame, held_keys, held_mouse_buttons));
+    }
+
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.recorder.take().map(|recorder| recorder.recording)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Feeds recorded events into the following `update` calls. Live input is ignored
+    /// until the replay finishes or `stop_replay` is called. Button states are replaced
+    /// by the ones saved with the recording
+    pub fn start_replay(&mut self, recording: InputRecording) {
+        self.keys.reset(&recording.held_keys);
+        self.mouse_buttons.reset(&recording.held_mouse_buttons);
+        self.replay = Some(Replay { recording, start_frame: self.frame, next_event: 0 });
+    }
+
+    /// Ends the replay and releases every button, live input is used again from the next event
+    pub fn stop_replay(&mut self) {
+        if self.replay.take().is_some() {
+            self.keys.reset(&[]);
+            self.mouse_buttons.reset(&[]);
+        }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Entry point for all live input, recorded when recording and dropped while replaying
+    pub fn handle_event(&mut self, event: InputEvent) {
+        if self.replay.is_some() {
+            return;
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.push(self.frame, event.clone());
+        }
+
+        self.apply(event);
+    }
+
+    fn apply(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Key { key, state } => self.keys.handle(key, state),
+            InputEvent::MouseButton { button, state } => self.mouse_buttons.handle(button, state),
+            InputEvent::CursorMoved { x, y } => {
+                self.cursor_position = Vector2::new(x as f32, y as f32) / self.scale_factor.0;
+            }
+            InputEvent::MouseMotion { dx, dy } => {
+                self.pending_motion = self.pending_motion + Vector2::new(dx as f32, dy as f32);
+            }
+            InputEvent::MouseWheel { delta } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => Vector2::new(x, y),
+                    MouseScrollDelta::PixelDelta(p) => Vector2::new(p.x as f32, p.y as f32) / PIXELS_PER_SCROLL_LINE,
+                };
+                self.pending_scroll = self.pending_scroll + lines;
+            }
+            InputEvent::Text { event } => self.pending_text.push(event),
+            InputEvent::Preedit { preedit } => self.preedit = preedit,
+        }
+    }
+
+    /// Gives access to the window for cursor grab and visibility controls
+    pub(crate) fn attach_window(&mut self, window: Arc<Window>) {
+        self.window = Some(window);
+    }
+
+    /// Sets the scale between window pixels and the logical units positions are reported in.
+    /// Kept up to date by `App`
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        let ratio = self.scale_factor.0 / scale_factor;
+        self.cursor_position = self.cursor_position * ratio;
+        self.previous_cursor_position = self.previous_cursor_position * ratio;
+        self.scale_factor = ScaleFactor(scale_factor);
+    }
+
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor.0
+    }
+
+    pub fn handle_key(&mut self, key: Key, state: ElementState) {
+        self.handle_event(InputEvent::Key { key, state });
+    }
+
+    /// Turns typed keys into text events, `text` is `KeyEvent::text`
+    pub fn handle_text_key(&mut self, logical_key: &LogicalKey, text: Option<&str>, state: ElementState) {
+        if let Some(event) = TextEvent::from_key(logical_key, text, state) {
+            self.handle_event(InputEvent::Text { event });
+        }
+    }
+
+    pub fn handle_ime(&mut self, ime: Ime) {
+        let (event, preedit) = TextEvent::from_ime(ime);
+        if let Some(event) = event {
+            self.handle_event(InputEvent::Text { event });
+        }
+        if preedit != self.preedit {
+            self.handle_event(InputEvent::Preedit { preedit });
+        }
+    }
+
+    pub fn handle_mouse_button(&mut self, button: MouseButton, state: ElementState) {
+        self.handle_event(InputEvent::MouseButton { button, state });
+    }
+
+    pub fn handle_cursor_moved(&mut self, position: PhysicalPosition<f64>) {
+        self.handle_event(InputEvent::CursorMoved { x: position.x, y: position.y });
+    }
+
+    /// Raw mouse movement from `DeviceEvent::MouseMotion`, reported even when the cursor is grabbed
+    pub fn handle_mouse_motion(&mut self, delta: (f64, f64)) {
+        self.handle_event(InputEvent::MouseMotion { dx: delta.0, dy: delta.1 });
+    }
+
+    pub fn handle_mouse_wheel(&mut self, delta: MouseScrollDelta) {
+        self.handle_event(InputEvent::MouseWheel { delta });
     }
 
     pub fn is_pressed(&self, key: Key) -> bool {
@@ -71,6 +384,266 @@ impl InputManager {
     }
 
     pub fn get_state(&self, key: Key) -> ButtonState {
-        self.key_states.get(&key).copied().unwrap_or(ButtonState::Up)
+        self.keys.get(key)
+    }
+
+    /// Keys pressed or held this frame, in no particular order
+    pub fn down_keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.keys.down()
     }
-}
\ No newline at end of file
+
+    pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+        self.get_mouse_state(button) == ButtonState::Pressed
+    }
+
+    pub fn is_mouse_held(&self, button: MouseButton) -> bool {
+        self.get_mouse_state(button) == ButtonState::Held
+    }
+
+    pub fn is_mouse_released(&self, button: MouseButton) -> bool {
+        self.get_mouse_state(button) == ButtonState::Released
+    }
+
+    pub fn is_mouse_up(&self, button: MouseButton) -> bool {
+        self.get_mouse_state(button) == ButtonState::Up
+    }
+
+    pub fn get_mouse_state(&self, button: MouseButton) -> ButtonState {
+        self.mouse_buttons.get(button)
+    }
+
+    /// Mouse buttons pressed or held this frame, in no particular order
+    pub fn down_mouse_buttons(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        self.mouse_buttons.down()
+    }
+
+    /// Cursor position in logical pixels, matching the renderer's default camera
+    pub fn cursor_position(&self) -> Vector2 {
+        self.cursor_position
+    }
+
+    /// Cursor position converted to world coordinates by `camera`
+    pub fn cursor_world_position(&self, camera: &Camera2D) -> Vector2 {
+        camera.screen_to_world(self.cursor_position)
+    }
+
+    /// How far the cursor moved in logical pixels since the last frame
+    pub fn cursor_delta(&self) -> Vector2 {
+        self.cursor_delta
+    }
+
+    /// Raw mouse movement since the last frame, keeps working while the cursor is grabbed
+    pub fn mouse_motion(&self) -> Vector2 {
+        self.mouse_motion
+    }
+
+    /// Scroll since the last frame in lines, positive `y` scrolls up
+    pub fn scroll(&self) -> Vector2 {
+        self.scroll
+    }
+
+    /// Text typed since the last frame, in order, including IME commits and editing keys
+    pub fn text_events(&self) -> &[TextEvent] {
+        &self.text_events
+    }
+
+    /// Printable text typed since the last frame
+    pub fn typed_text(&self) -> String {
+        self.text_events
+            .iter()
+            .filter_map(|event| match event {
+                TextEvent::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Applies this frame's text events to `target`, returns `true` if Enter was pressed
+    pub fn edit_text(&self, target: &mut String) -> bool {
+        let mut enter = false;
+        for event in &self.text_events {
+            enter |= event.apply_to(target);
+        }
+        enter
+    }
+
+    /// Composition currently shown by the input method, draw it after the edited text
+    pub fn preedit(&self) -> Option<&Preedit> {
+        self.preedit.as_ref()
+    }
+
+    /// Enables input methods (e.g. for CJK languages) while a text field is focused
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        if let Some(window) = &self.window {
+            window.set_ime_allowed(allowed);
+        }
+    }
+
+    /// Tells the input method where the edited text is, in logical pixels, so its popup doesn't cover it
+    pub fn set_ime_cursor_area(&self, position: Vector2, size: Vector2) {
+        if let Some(window) = &self.window {
+            let position = position * self.scale_factor.0;
+            let size = size * self.scale_factor.0;
+            window.set_ime_cursor_area(
+                PhysicalPosition::new(position.x as f64, position.y as f64),
+                PhysicalSize::new(size.x as f64, size.y as f64),
+            );
+        }
+    }
+
+    /// Locks the cursor inside the window, falling back to confining it when locking is unsupported.
+    /// Returns `false` when the platform supports neither
+    pub fn set_cursor_grab(&self, grab: bool) -> bool {
+        let Some(window) = &self.window else { return false };
+
+        if !grab {
+            return window.set_cursor_grab(CursorGrabMode::None).is_ok();
+        }
+
+        window.set_cursor_grab(CursorGrabMode::Locked)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+            .is_ok()
+    }
+
+    pub fn set_cursor_visible(&self, visible: bool) {
+        if let Some(window) = &self.window {
+            window.set_cursor_visible(visible);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::keyboard::KeyCode;
+
+    const KEY: Key = Key::Code(KeyCode::KeyA);
+
+    #[test]
+    fn key_tapped_within_one_frame_is_pressed_then_released() {
+        let mut input = InputManager::default();
+        input.handle_key(KEY, ElementState::Pressed);
+        input.handle_key(KEY, ElementState::Released);
+
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Pressed);
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Released);
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Up);
+    }
+
+    #[test]
+    fn mouse_click_within_one_frame_is_pressed_then_released() {
+        let mut input = InputManager::default();
+        input.handle_mouse_button(MouseButton::Left, ElementState::Pressed);
+        input.handle_mouse_button(MouseButton::Left, ElementState::Released);
+
+        input.update();
+        assert_eq!(input.get_mouse_state(MouseButton::Left), ButtonState::Pressed);
+        input.update();
+        assert_eq!(input.get_mouse_state(MouseButton::Left), ButtonState::Released);
+        input.update();
+        assert_eq!(input.get_mouse_state(MouseButton::Left), ButtonState::Up);
+    }
+
+    #[test]
+    fn held_key_released_and_pressed_within_one_frame_stays_held() {
+        let mut input = InputManager::default();
+        input.handle_key(KEY, ElementState::Pressed);
+        input.update();
+        input.update();
+
+        input.handle_key(KEY, ElementState::Released);
+        input.handle_key(KEY, ElementState::Pressed);
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Held);
+    }
+
+    #[test]
+    fn regular_press_and_release() {
+        let mut input = InputManager::default();
+        input.handle_key(KEY, ElementState::Pressed);
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Pressed);
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Held);
+
+        input.handle_key(KEY, ElementState::Released);
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Released);
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Up);
+    }
+
+    #[test]
+    fn replay_restores_buttons_held_when_recording_started() {
+        let mut input = InputManager::default();
+        input.handle_key(KEY, ElementState::Pressed);
+        input.update();
+        input.start_recording();
+        input.handle_key(KEY, ElementState::Released);
+        input.update();
+        let recording = input.stop_recording().unwrap();
+        assert_eq!(recording.held_keys, vec![KEY]);
+        let recording = InputRecording::from_toml(&recording.to_toml().unwrap()).unwrap();
+
+        input.start_replay(recording);
+        assert_eq!(input.get_state(KEY), ButtonState::Held);
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Released);
+    }
+
+    #[test]
+    fn key_down_when_replay_starts_is_released_after_it() {
+        const F6: Key = Key::Code(KeyCode::F6);
+        let mut input = InputManager::default();
+        input.start_recording();
+        input.update();
+        let recording = input.stop_recording().unwrap();
+
+        input.handle_key(F6, ElementState::Pressed);
+        input.update();
+        input.start_replay(recording);
+        assert_eq!(input.get_state(F6), ButtonState::Up);
+
+        // Release arrives during the replay and is dropped
+        input.handle_key(F6, ElementState::Released);
+        input.update();
+        input.update();
+        assert!(!input.is_replaying());
+        assert_eq!(input.get_state(F6), ButtonState::Up);
+
+        input.handle_key(F6, ElementState::Pressed);
+        input.update();
+        assert_eq!(input.get_state(F6), ButtonState::Pressed);
+    }
+
+    #[test]
+    fn frame_without_game_update_carries_transitions_over() {
+        let mut input = InputManager::default();
+        input.handle_key(KEY, ElementState::Pressed);
+        input.handle_mouse_motion((3.0, 0.0));
+        input.update();
+        input.carry_over();
+
+        input.handle_key(KEY, ElementState::Released);
+        input.handle_mouse_motion((2.0, 0.0));
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Pressed);
+        assert_eq!(input.mouse_motion().x, 5.0);
+
+        input.update();
+        assert_eq!(input.get_state(KEY), ButtonState::Released);
+        assert_eq!(input.mouse_motion().x, 0.0);
+    }
+
+    #[test]
+    fn settle_hides_transitions_from_further_steps() {
+        let mut input = InputManager::default();
+        input.handle_key(KEY, ElementState::Pressed);
+        input.update();
+        input.settle();
+        assert_eq!(input.get_state(KEY), ButtonState::Held);
+    }
+}
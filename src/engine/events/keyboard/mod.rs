@@ -1 +1,3 @@
-pub mod ButtonState;
\ No newline at end of file
+#[allow(non_snake_case)]
+pub mod ButtonState;
+pub mod text_input;
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use winit::event::{ElementState, Ime};
+use winit::keyboard::{Key as LogicalKey, NamedKey};
+
+/// Single text editing step, produced from typed keys and IME commits
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextEvent {
+    /// Printable text, one or more characters
+    Text(String),
+    Backspace,
+    Delete,
+    Enter,
+}
+
+/// Text being composed by an input method, not yet committed
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preedit {
+    pub text: String,
+    /// Byte range of the IME cursor or selection inside `text`
+    pub cursor: Option<(usize, usize)>,
+}
+
+impl TextEvent {
+    /// Translates a key press into a text event. Key repeats produce events too,
+    /// so holding backspace keeps deleting
+    pub fn from_key(logical_key: &LogicalKey, text: Option<&str>, state: ElementState) -> Option<Self> {
+        if state != ElementState::Pressed {
+            return None;
+        }
+
+        match logical_key {
+            LogicalKey::Named(NamedKey::Backspace) => Some(TextEvent::Backspace),
+            LogicalKey::Named(NamedKey::Delete) => Some(TextEvent::Delete),
+            LogicalKey::Named(NamedKey::Enter) => Some(TextEvent::Enter),
+            _ => {
+                let text: String = text?.chars().filter(|c| !c.is_control()).collect();
+                (!text.is_empty()).then_some(TextEvent::Text(text))
+            }
+        }
+    }
+
+    /// Translates an IME event, returning committed text and the new preedit state
+    pub fn from_ime(ime: Ime) -> (Option<Self>, Option<Preedit>) {
+        match ime {
+            Ime::Commit(text) => (Some(TextEvent::Text(text)), None),
+            Ime::Preedit(text, _) if text.is_empty() => (None, None),
+            Ime::Preedit(text, cursor) => (None, Some(Preedit { text, cursor })),
+            Ime::Enabled | Ime::Disabled => (None, None),
+        }
+    }
+
+    /// Applies the event to a single-line string edited at its end.
+    /// Returns `true` for `Enter`
+    pub fn apply_to(&self, target: &mut String) -> bool {
+        match self {
+            TextEvent::Text(text) => target.push_str(text),
+            TextEvent::Backspace => {
+                target.pop();
+            }
+            TextEvent::Delete => {}
+            TextEvent::Enter => return true,
+        }
+        false
+    }
+}
@@ -1 +1,3 @@
-pub mod keyboard;
\ No newline at end of file
+pub mod keyboard;
+pub mod actions;
+pub mod recording;
@@ -0,0 +1,157 @@
+use std::fmt;
+use std::path::Path;
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use winit::event::{ElementState, MouseScrollDelta};
+use crate::engine::events::keyboard::ButtonState::{Key, MouseButton};
+use crate::engine::events::keyboard::text_input::{Preedit, TextEvent};
+
+/// Input delivered to `InputManager`, in the form it is recorded and replayed
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum InputEvent {
+    Key { key: Key, state: ElementState },
+    MouseButton { button: MouseButton, state: ElementState },
+    CursorMoved { x: f64, y: f64 },
+    MouseMotion { dx: f64, dy: f64 },
+    MouseWheel { delta: MouseScrollDelta },
+    Text { event: TextEvent },
+    Preedit { preedit: Option<Preedit> },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Number of `InputManager::update` calls since recording started
+    pub frame: u64,
+    /// Seconds since recording started
+    pub time: f32,
+    #[serde(flatten)]
+    pub event: InputEvent,
+}
+
+/// Recorded input session, frame based so replays are independent of real time
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// Keys that were down when recording started, restored when the replay starts
+    #[serde(default)]
+    pub held_keys: Vec<Key>,
+    /// Mouse buttons that were down when recording started
+    #[serde(default)]
+    pub held_mouse_buttons: Vec<MouseButton>,
+    /// Delta time of every recorded frame, replayed instead of the measured one
+    pub frame_times: Vec<f32>,
+    pub events: Vec<RecordedEvent>,
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingError::Io(e) => write!(f, "failed to access recording: {e}"),
+            RecordingError::Parse(e) => write!(f, "failed to parse recording: {e}"),
+            RecordingError::Serialize(e) => write!(f, "failed to serialize recording: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(e: std::io::Error) -> Self {
+        RecordingError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for RecordingError {
+    fn from(e: toml::de::Error) -> Self {
+        RecordingError::Parse(e)
+    }
+}
+
+impl From<toml::ser::Error> for RecordingError {
+    fn from(e: toml::ser::Error) -> Self {
+        RecordingError::Serialize(e)
+    }
+}
+
+impl InputRecording {
+    pub fn from_toml(source: &str) -> Result<Self, RecordingError> {
+        Ok(toml::from_str(source)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, RecordingError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RecordingError> {
+        std::fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    /// Number of frames covered by the recording
+    pub fn frames(&self) -> u64 {
+        let last_event = self.events.last().map_or(0, |e| e.frame + 1);
+        last_event.max(self.frame_times.len() as u64)
+    }
+}
+
+/// Recording in progress
+pub(crate) struct Recorder {
+    pub recording: InputRecording,
+    pub start_frame: u64,
+    start_time: Instant,
+}
+
+impl Recorder {
+    pub fn new(start_frame: u64, held_keys: Vec<Key>, held_mouse_buttons: Vec<MouseButton>) -> Self {
+        let recording = InputRecording { held_keys, held_mouse_buttons, ..InputRecording::default() };
+        Self { recording, start_frame, start_time: Instant::now() }
+    }
+
+    pub fn push(&mut self, frame: u64, event: InputEvent) {
+        self.recording.events.push(RecordedEvent {
+            frame: frame - self.start_frame,
+            time: self.start_time.elapsed().as_secs_f32(),
+            event,
+        });
+    }
+}
+
+/// Replay in progress
+pub(crate) struct Replay {
+    pub recording: InputRecording,
+    pub start_frame: u64,
+    pub next_event: usize,
+}
+
+impl Replay {
+    /// Takes events recorded for `frame`, in the original order
+    pub fn events_for(&mut self, frame: u64) -> Vec<InputEvent> {
+        let frame = frame - self.start_frame;
+        let mut events = Vec::new();
+
+        while let Some(recorded) = self.recording.events.get(self.next_event) {
+            if recorded.frame > frame {
+                break;
+            }
+            events.push(recorded.event.clone());
+            self.next_event += 1;
+        }
+
+        events
+    }
+
+    pub fn is_finished(&self, frame: u64) -> bool {
+        frame - self.start_frame >= self.recording.frames()
+    }
+}
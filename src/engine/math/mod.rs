@@ -1,2 +1,4 @@
 pub mod vector2;
-pub mod vector2int;
\ No newline at end of file
+pub mod vector2int;
+pub mod rect;
+pub mod transform;
\ No newline at end of file
@@ -0,0 +1,31 @@
+use crate::engine::math::vector2::Vector2;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Rect {x, y, width, height}
+    }
+
+    /// Returns top-left corner of the rectangle
+    pub fn position(&self) -> Vector2 {
+        Vector2::new(self.x, self.y)
+    }
+
+    /// Returns width and height as a vector
+    pub fn size(&self) -> Vector2 {
+        Vector2::new(self.width, self.height)
+    }
+
+    /// Checks if point lies inside the rectangle
+    pub fn contains(&self, point: Vector2) -> bool {
+        point.x >= self.x && point.x < self.x + self.width
+            && point.y >= self.y && point.y < self.y + self.height
+    }
+}
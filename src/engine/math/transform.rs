@@ -0,0 +1,99 @@
+use std::ops::Mul;
+use crate::engine::math::vector2::Vector2;
+
+/// 2D affine transform mapping `(x, y)` to `(a * x + c * y + tx, b * x + d * y + ty)`.
+///
+/// `first * second` applies `second` first, so `parent * child` places a child
+/// in its parent's space
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform2D {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform2D {
+    pub fn new(a: f32, b: f32, c: f32, d: f32, tx: f32, ty: f32) -> Self {
+        Transform2D { a, b, c, d, tx, ty }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn translation(offset: Vector2) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, offset.x, offset.y)
+    }
+
+    /// Rotation in radians, clockwise on screen since `y` points down
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    pub fn scaling(scale: Vector2) -> Self {
+        Self::new(scale.x, 0.0, 0.0, scale.y, 0.0, 0.0)
+    }
+
+    pub fn transform_point(&self, point: Vector2) -> Vector2 {
+        Vector2::new(
+            self.a * point.x + self.c * point.y + self.tx,
+            self.b * point.x + self.d * point.y + self.ty,
+        )
+    }
+
+    /// Transforms a direction, ignoring translation
+    pub fn transform_vector(&self, vector: Vector2) -> Vector2 {
+        Vector2::new(self.a * vector.x + self.c * vector.y, self.b * vector.x + self.d * vector.y)
+    }
+
+    /// Transform undoing this one, `None` when it collapses space (e.g. zero scale)
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Self::new(a, b, c, d, -(a * self.tx + c * self.ty), -(b * self.tx + d * self.ty)))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+}
+
+impl Mul for Transform2D {
+    type Output = Transform2D;
+
+    fn mul(self, rhs: Transform2D) -> Transform2D {
+        Transform2D::new(
+            self.a * rhs.a + self.c * rhs.b,
+            self.b * rhs.a + self.d * rhs.b,
+            self.a * rhs.c + self.c * rhs.d,
+            self.b * rhs.c + self.d * rhs.d,
+            self.a * rhs.tx + self.c * rhs.ty + self.tx,
+            self.b * rhs.tx + self.d * rhs.ty + self.ty,
+        )
+    }
+}
+
+impl Mul<Vector2> for Transform2D {
+    type Output = Vector2;
+
+    fn mul(self, rhs: Vector2) -> Vector2 {
+        self.transform_point(rhs)
+    }
+}
@@ -2,7 +2,7 @@ use std::ops::{Add, Sub, Mul, Div};
 use bytemuck::{Pod, Zeroable};
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Pod, Zeroable)]
 pub struct Vector2 {
     pub x: f32,
     pub y: f32,
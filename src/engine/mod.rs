@@ -1,3 +1,6 @@
 pub mod math;
 pub mod renderer;
-pub mod events;
\ No newline at end of file
+pub mod events;
+pub mod time;
+pub mod window;
+pub mod error;
\ No newline at end of file
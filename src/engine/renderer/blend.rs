@@ -0,0 +1,54 @@
+/// How drawn colors are combined with what is already on screen
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Standard transparency using the alpha channel
+    #[default]
+    Alpha,
+    /// Adds color on top, scaled by alpha. Good for glows, fire and particles
+    Additive,
+    /// Multiplies screen color by drawn color. Good for shadows and tinting
+    Multiply,
+    /// Transparency for colors already multiplied by their alpha
+    Premultiplied,
+    /// Overwrites screen color, ignoring alpha
+    Replace,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 5] = [
+        BlendMode::Alpha,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Premultiplied,
+        BlendMode::Replace,
+    ];
+
+    pub fn to_blend_state(self) -> wgpu::BlendState {
+        match self {
+            BlendMode::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::OVER,
+            },
+            BlendMode::Multiply => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Dst,
+                    dst_factor: wgpu::BlendFactor::Zero,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::OVER,
+            },
+            BlendMode::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::Replace => wgpu::BlendState::REPLACE,
+        }
+    }
+
+    /// Position in `BlendMode::ALL`, used to pick the pipeline
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
@@ -0,0 +1,75 @@
+use bytemuck::{Pod, Zeroable};
+use crate::engine::math::vector2::Vector2;
+
+/// 2D camera mapping world coordinates to the screen.
+///
+/// `position` is the world point shown in the center of the viewport,
+/// `zoom` scales the world (2.0 = everything twice as big) and `rotation`
+/// (in radians) spins the world around `position`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera2D {
+    pub position: Vector2,
+    pub zoom: f32,
+    pub rotation: f32,
+    /// Visible area in logical pixels
+    pub viewport: Vector2,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub(crate) struct CameraUniform {
+    pub view_proj: [[f32; 4]; 4],
+}
+
+impl Camera2D {
+    /// Camera where world units equal logical pixels and (0, 0) is the top-left corner
+    pub fn new(viewport: Vector2) -> Self {
+        Self {
+            position: viewport / 2.0,
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport,
+        }
+    }
+
+    /// Converts world position to logical window pixels
+    pub fn world_to_screen(&self, world: Vector2) -> Vector2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let d = world - self.position;
+        let rotated = Vector2::new(d.x * cos + d.y * sin, -d.x * sin + d.y * cos);
+        rotated * self.zoom + self.viewport / 2.0
+    }
+
+    /// Converts logical window pixels to world position
+    pub fn screen_to_world(&self, screen: Vector2) -> Vector2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let d = (screen - self.viewport / 2.0) / self.zoom;
+        Vector2::new(d.x * cos - d.y * sin, d.x * sin + d.y * cos) + self.position
+    }
+
+    /// Column-major matrix taking world coordinates straight to NDC
+    pub fn view_projection(&self) -> [[f32; 4]; 4] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let sx = 2.0 * self.zoom / self.viewport.x;
+        let sy = -2.0 * self.zoom / self.viewport.y;
+
+        // ndc = S * R(-rotation) * (world - position)
+        let m00 = sx * cos;
+        let m01 = sx * sin;
+        let m10 = -sy * sin;
+        let m11 = sy * cos;
+        let tx = -(m00 * self.position.x + m01 * self.position.y);
+        let ty = -(m10 * self.position.x + m11 * self.position.y);
+
+        [
+            [m00, m10, 0.0, 0.0],
+            [m01, m11, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [tx, ty, 0.0, 1.0],
+        ]
+    }
+
+    pub(crate) fn uniform(&self) -> CameraUniform {
+        CameraUniform { view_proj: self.view_projection() }
+    }
+}
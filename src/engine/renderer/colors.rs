@@ -1,5 +1,3 @@
-use wgpu::core::command::AttachmentErrorLocation::Color;
-
 #[derive(Copy, Clone, Debug)]
 pub enum Colors {
     Red,
@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use crate::engine::math::rect::Rect;
+use crate::engine::math::vector2::Vector2;
+use crate::engine::renderer::texture::{Image, Texture, TextureError};
+
+/// Characters rasterized from TTF/OTF fonts: printable ASCII, Latin-1 and Latin Extended-A
+pub const DEFAULT_CHARSET: &[(char, char)] = &[(' ', '~'), ('\u{a0}', '\u{17f}')];
+
+const ATLAS_MIN_SIZE: u32 = 64;
+const ATLAS_PADDING: u32 = 1;
+
+/// Glyph placement in pixels at the font's native `size`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Glyph {
+    /// Area of the atlas texture
+    pub source: Rect,
+    /// Glyph top-left relative to pen position at the top of the line
+    pub offset: Vector2,
+    pub advance: f32,
+}
+
+/// Glyph atlas uploaded to the `Renderer`, created with `Renderer::load_font*` or `Renderer::load_bmfont*`
+pub struct Font {
+    pub texture: Texture,
+    /// Pixel size glyphs were rasterized at, drawing at other sizes scales them
+    pub size: f32,
+    pub line_height: f32,
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), f32>,
+}
+
+/// Font data decoded on the CPU, waiting to be uploaded
+pub(crate) struct FontData {
+    pub atlas: Image,
+    pub size: f32,
+    pub line_height: f32,
+    pub glyphs: HashMap<char, Glyph>,
+    pub kerning: HashMap<(char, char), f32>,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Options for `Renderer::draw_text_ex`
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TextLayout {
+    pub align: TextAlign,
+    /// Wraps lines longer than this. Alignment is then done inside `[pos.x, pos.x + max_width]`,
+    /// otherwise lines are aligned around `pos.x`
+    pub max_width: Option<f32>,
+}
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(std::io::Error),
+    Texture(TextureError),
+    Ttf(&'static str),
+    BmFont(String),
+    /// Glyphs don't fit into a texture of the largest size the GPU supports
+    AtlasTooLarge { width: u32, height: u32, max: u32 },
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(e) => write!(f, "failed to read font: {e}"),
+            FontError::Texture(e) => write!(f, "failed to load font page: {e}"),
+            FontError::Ttf(e) => write!(f, "failed to parse font: {e}"),
+            FontError::BmFont(e) => write!(f, "failed to parse BMFont: {e}"),
+            FontError::AtlasTooLarge { width, height, max } => {
+                write!(f, "font atlas of {width}x{height} exceeds the maximum texture size {max}, use a smaller font size")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+impl From<std::io::Error> for FontError {
+    fn from(e: std::io::Error) -> Self {
+        FontError::Io(e)
+    }
+}
+
+impl From<TextureError> for FontError {
+    fn from(e: TextureError) -> Self {
+        FontError::Texture(e)
+    }
+}
+
+impl Font {
+    pub(crate) fn new(texture: Texture, data: FontData) -> Self {
+        Self {
+            texture,
+            size: data.size,
+            line_height: data.line_height,
+            glyphs: data.glyphs,
+            kerning: data.kerning,
+        }
+    }
+
+    /// Glyph for `c`, falling back to `?` for characters missing from the atlas
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?'))
+    }
+
+    /// Extra advance between two characters, in pixels at the native size
+    pub fn kerning(&self, left: char, right: char) -> f32 {
+        self.kerning.get(&(left, right)).copied().unwrap_or(0.0)
+    }
+
+    /// Width of a single line (newlines are not handled) drawn at `size`
+    pub fn line_width(&self, line: &str, size: f32) -> f32 {
+        let mut width = 0.0;
+        let mut prev = None;
+
+        for c in line.chars() {
+            if let Some(prev) = prev {
+                width += self.kerning(prev, c);
+            }
+            if let Some(glyph) = self.glyph(c) {
+                width += glyph.advance;
+            }
+            prev = Some(c);
+        }
+
+        width * size / self.size
+    }
+
+    /// Splits text into lines on `\n` and, when `max_width` is set, wraps words that don't fit.
+    /// Words longer than `max_width` are broken between characters
+    pub fn wrap(&self, text: &str, size: f32, max_width: Option<f32>) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for paragraph in text.split('\n') {
+            let Some(max_width) = max_width else {
+                lines.push(paragraph.to_string());
+                continue;
+            };
+
+            let mut line = String::new();
+            for word in paragraph.split(' ') {
+                let candidate = if line.is_empty() { word.to_string() } else { format!("{line} {word}") };
+                if self.line_width(&candidate, size) <= max_width {
+                    line = candidate;
+                    continue;
+                }
+
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+
+                for c in word.chars() {
+                    line.push(c);
+                    if self.line_width(&line, size) > max_width && line.chars().count() > 1 {
+                        line.pop();
+                        lines.push(std::mem::take(&mut line));
+                        line.push(c);
+                    }
+                }
+            }
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    /// Size of the text block drawn at `size`
+    pub fn measure(&self, text: &str, size: f32, max_width: Option<f32>) -> Vector2 {
+        let lines = self.wrap(text, size, max_width);
+        let width = lines.iter().map(|line| self.line_width(line, size)).fold(0.0, f32::max);
+
+        Vector2::new(width, lines.len() as f32 * self.line_height * size / self.size)
+    }
+}
+
+impl FontData {
+    /// Rasterizes TTF/OTF font at `size` pixels into an atlas no larger than `max_dimension` on either side
+    pub fn from_ttf_bytes(bytes: &[u8], size: f32, charset: &[(char, char)], max_dimension: u32) -> Result<Self, FontError> {
+        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings {
+            scale: size,
+            ..Default::default()
+        }).map_err(FontError::Ttf)?;
+
+        let line_metrics = font.horizontal_line_metrics(size).ok_or(FontError::Ttf("font has no horizontal metrics"))?;
+        let chars: Vec<char> = charset
+            .iter()
+            .flat_map(|&(first, last)| first..=last)
+            .filter(|&c| font.lookup_glyph_index(c) != 0 || c == ' ')
+            .collect();
+
+        let bitmaps: Vec<_> = chars.iter().map(|&c| font.rasterize(c, size)).collect();
+        let sizes: Vec<_> = bitmaps.iter().map(|(m, _)| (m.width as u32, m.height as u32)).collect();
+        let AtlasLayout { positions, width: atlas_width, height: atlas_height } = pack(&sizes, max_dimension)?;
+
+        let mut atlas = Image {
+            width: atlas_width,
+            height: atlas_height,
+            pixels: vec![0; (atlas_width * atlas_height * 4) as usize],
+        };
+        let mut glyphs = HashMap::with_capacity(chars.len());
+
+        for ((&c, (metrics, bitmap)), &(x, y)) in chars.iter().zip(&bitmaps).zip(&positions) {
+            // Glyphs are white, coverage goes to alpha so the draw color tints them
+            for (i, &coverage) in bitmap.iter().enumerate() {
+                let px = x + (i % metrics.width) as u32;
+                let py = y + (i / metrics.width) as u32;
+                let index = ((py * atlas_width + px) * 4) as usize;
+                atlas.pixels[index..index + 4].copy_from_slice(&[255, 255, 255, coverage]);
+            }
+
+            glyphs.insert(c, Glyph {
+                source: Rect::new(x as f32, y as f32, metrics.width as f32, metrics.height as f32),
+                offset: Vector2::new(
+                    metrics.xmin as f32,
+                    line_metrics.ascent - (metrics.ymin as f32 + metrics.height as f32),
+                ),
+                advance: metrics.advance_width,
+            });
+        }
+
+        let mut kerning = HashMap::new();
+        for &left in &chars {
+            for &right in &chars {
+                if let Some(amount) = font.horizontal_kern(left, right, size).filter(|&k| k != 0.0) {
+                    kerning.insert((left, right), amount);
+                }
+            }
+        }
+
+        Ok(Self { atlas, size, line_height: line_metrics.new_line_size, glyphs, kerning })
+    }
+
+    /// Parses text BMFont descriptor with a single PNG page
+    pub fn from_bmfont(descriptor: &str, page: &[u8]) -> Result<Self, FontError> {
+        let mut size = None;
+        let mut line_height = None;
+        let mut glyphs = HashMap::new();
+        let mut kerning = HashMap::new();
+
+        for line in descriptor.lines() {
+            let mut parts = line.split_whitespace();
+            let Some(tag) = parts.next() else { continue };
+            let attributes = parse_attributes(line);
+            let int = |key: &str| -> Result<i32, FontError> {
+                attributes
+                    .get(key)
+                    .ok_or_else(|| FontError::BmFont(format!("`{tag}` is missing `{key}`")))?
+                    .parse()
+                    .map_err(|_| FontError::BmFont(format!("`{tag}` has invalid `{key}`")))
+            };
+
+            match tag {
+                "info" => size = Some(int("size")?.unsigned_abs() as f32),
+                "common" => {
+                    line_height = Some(int("lineHeight")? as f32);
+                    if int("pages")? != 1 {
+                        return Err(FontError::BmFont("only single page fonts are supported".to_string()));
+                    }
+                }
+                "char" => {
+                    let id = int("id")?;
+                    let c = char::from_u32(id as u32)
+                        .ok_or_else(|| FontError::BmFont(format!("invalid character id {id}")))?;
+                    glyphs.insert(c, Glyph {
+                        source: Rect::new(int("x")? as f32, int("y")? as f32, int("width")? as f32, int("height")? as f32),
+                        offset: Vector2::new(int("xoffset")? as f32, int("yoffset")? as f32),
+                        advance: int("xadvance")? as f32,
+                    });
+                }
+                "kerning" => {
+                    let first = char::from_u32(int("first")? as u32);
+                    let second = char::from_u32(int("second")? as u32);
+                    if let (Some(first), Some(second)) = (first, second) {
+                        kerning.insert((first, second), int("amount")? as f32);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let line_height = line_height.ok_or_else(|| FontError::BmFont("missing `common` line".to_string()))?;
+        let atlas = Image::from_png_bytes(page)?;
+
+        Ok(Self {
+            atlas,
+            size: size.unwrap_or(line_height),
+            line_height,
+            glyphs,
+            kerning,
+        })
+    }
+
+    /// Reads BMFont descriptor and the page it references, relative to the descriptor
+    pub fn from_bmfont_file(path: &Path) -> Result<Self, FontError> {
+        let descriptor = std::fs::read_to_string(path)?;
+        let page = descriptor
+            .lines()
+            .find(|line| line.starts_with("page "))
+            .and_then(|line| parse_attributes(line).remove("file"))
+            .ok_or_else(|| FontError::BmFont("missing `page` line".to_string()))?;
+
+        let page_path = path.parent().unwrap_or(Path::new("")).join(page);
+        let page = std::fs::read(page_path)?;
+
+        Self::from_bmfont(&descriptor, &page)
+    }
+}
+
+/// Parses `key=value` and `key="quoted value"` pairs of a BMFont line
+fn parse_attributes(line: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = line;
+
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].rsplit(' ').next().unwrap_or("").to_string();
+        rest = &rest[eq + 1..];
+
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let value = &quoted[..end];
+            rest = quoted.get(end + 1..).unwrap_or("");
+            value
+        } else {
+            let end = rest.find(' ').unwrap_or(rest.len());
+            let value = &rest[..end];
+            rest = &rest[end..];
+            value
+        };
+
+        attributes.insert(key, value.to_string());
+    }
+
+    attributes
+}
+
+/// Glyph positions and the size of the atlas they were packed into
+struct AtlasLayout {
+    positions: Vec<(u32, u32)>,
+    width: u32,
+    height: u32,
+}
+
+/// Shelf-packs rectangles into a roughly square power of two atlas.
+/// Fails when the atlas would exceed `max_dimension`
+fn pack(sizes: &[(u32, u32)], max_dimension: u32) -> Result<AtlasLayout, FontError> {
+    let area: u64 = sizes.iter().map(|&(w, h)| (w + ATLAS_PADDING) as u64 * (h + ATLAS_PADDING) as u64).sum();
+    let widest = sizes.iter().map(|&(w, _)| w + 2 * ATLAS_PADDING).max().unwrap_or(0);
+
+    let mut width = ((area as f64).sqrt().ceil() as u32).max(widest).max(ATLAS_MIN_SIZE).next_power_of_two();
+    loop {
+        let (positions, used_height) = pack_shelves(sizes, width);
+        let height = used_height.max(ATLAS_MIN_SIZE).next_power_of_two();
+
+        if width.max(height) > max_dimension {
+            return Err(FontError::AtlasTooLarge { width, height, max: max_dimension });
+        }
+        // Shelves waste space, so widen the atlas until it's no longer taller than wide
+        if height <= width || width * 2 > max_dimension {
+            return Ok(AtlasLayout { positions, width, height });
+        }
+        width *= 2;
+    }
+}
+
+/// Places rectangles left to right in rows of the tallest rectangle, returning positions and used height
+fn pack_shelves(sizes: &[(u32, u32)], atlas_width: u32) -> (Vec<(u32, u32)>, u32) {
+    let mut positions = Vec::with_capacity(sizes.len());
+    let (mut x, mut y, mut shelf_height) = (ATLAS_PADDING, ATLAS_PADDING, 0);
+
+    for &(width, height) in sizes {
+        if x + width + ATLAS_PADDING > atlas_width {
+            x = ATLAS_PADDING;
+            y += shelf_height + ATLAS_PADDING;
+            shelf_height = 0;
+        }
+
+        positions.push((x, y));
+        x += width + ATLAS_PADDING;
+        shelf_height = shelf_height.max(height);
+    }
+
+    (positions, y + shelf_height + ATLAS_PADDING)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlaps(a: ((u32, u32), (u32, u32)), b: ((u32, u32), (u32, u32))) -> bool {
+        let (((ax, ay), (aw, ah)), ((bx, by), (bw, bh))) = (a, b);
+        ax < bx + bw && bx < ax + aw && ay < by + bh && by < ay + ah
+    }
+
+    #[test]
+    fn atlas_is_roughly_square_power_of_two() {
+        let sizes = vec![(40, 50); 200];
+        let layout = pack(&sizes, 16384).unwrap();
+
+        assert!(layout.width.is_power_of_two() && layout.height.is_power_of_two());
+        assert!(layout.height <= layout.width && layout.width <= layout.height * 2);
+        for (i, &a) in layout.positions.iter().enumerate() {
+            assert!(a.0 + 40 <= layout.width && a.1 + 50 <= layout.height);
+            for &b in &layout.positions[i + 1..] {
+                assert!(!overlaps((a, (40, 50)), (b, (40, 50))));
+            }
+        }
+    }
+
+    #[test]
+    fn wide_glyph_fits_inside_the_atlas() {
+        let layout = pack(&[(10, 10), (700, 20), (10, 10)], 16384).unwrap();
+        assert!(layout.width >= 702);
+        assert!(layout.positions.iter().all(|&(x, _)| x + 10 <= layout.width));
+        assert!(layout.positions[1].0 + 700 + ATLAS_PADDING <= layout.width);
+    }
+
+    #[test]
+    fn atlas_larger_than_the_limit_is_an_error() {
+        let sizes = vec![(200, 200); 100];
+        assert!(matches!(pack(&sizes, 1024), Err(FontError::AtlasTooLarge { max: 1024, .. })));
+        assert!(pack(&[(2000, 10)], 1024).is_err());
+    }
+}
@@ -1,2 +1,10 @@
+#[allow(clippy::module_inception)]
 pub mod renderer;
 pub mod colors;
+pub mod texture;
+pub mod offscreen;
+pub mod camera;
+pub mod blend;
+pub mod font;
+pub mod stroke;
+pub mod tessellate;
@@ -0,0 +1,242 @@
+use std::path::Path;
+use std::sync::mpsc;
+use crate::engine::renderer::renderer::Renderer;
+use crate::engine::renderer::texture::{Image, TextureError};
+
+/// Texture that can be rendered into instead of the window surface and read back to the CPU
+pub struct OffscreenTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl OffscreenTarget {
+    /// Creates target matching the renderer's output format and physical size,
+    /// so drawing covers it exactly. Create a new target after `Renderer::resize`
+    pub fn new(renderer: &Renderer) -> Self {
+        let (width, height) = renderer.physical_size();
+        let texture = renderer.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: renderer.surface_config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self { texture, view, width, height }
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// Copies target contents to the CPU, blocking until the GPU is done
+    pub fn read_pixels(&self, renderer: &Renderer) -> Result<Image, TextureError> {
+        let unpadded_row = 4 * self.width;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = unpadded_row.div_ceil(align) * align;
+
+        let buffer = renderer.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: (padded_row * self.height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = renderer.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Readback Encoder"),
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+        );
+
+        renderer.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        renderer.device.poll(wgpu::Maintain::Wait);
+        // The callback always runs during the wait, a missing result means the device was lost
+        receiver.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
+
+        let mut pixels = Vec::with_capacity((unpadded_row * self.height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks_exact(padded_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_row as usize]);
+            }
+        }
+        buffer.unmap();
+
+        let bgra = matches!(
+            self.texture.format(),
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        if bgra {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Ok(Image { width: self.width, height: self.height, pixels })
+    }
+
+    /// Reads target back and writes it to PNG file
+    pub fn save_png(&self, renderer: &Renderer, path: impl AsRef<Path>) -> Result<(), TextureError> {
+        self.read_pixels(renderer)?.save_png(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::math::vector2::Vector2;
+    use crate::engine::renderer::colors::Colors;
+
+    /// Headless renderer for GPU tests. Without an adapter the test fails, unless
+    /// `YUGIN_SKIP_GPU_TESTS` is set, then `None` is returned and the test is skipped
+    fn headless(width: u32, height: u32) -> Option<Renderer> {
+        match pollster::block_on(Renderer::headless(width, height, 1_000)) {
+            Ok(renderer) => Some(renderer),
+            Err(e) if std::env::var_os("YUGIN_SKIP_GPU_TESTS").is_some() => {
+                eprintln!("{e}, skipping because YUGIN_SKIP_GPU_TESTS is set");
+                None
+            }
+            Err(e) => panic!("{e}, install a software adapter such as llvmpipe or set YUGIN_SKIP_GPU_TESTS"),
+        }
+    }
+
+    fn pixel(image: &Image, x: u32, y: u32) -> [u8; 4] {
+        let index = ((y * image.width + x) * 4) as usize;
+        image.pixels[index..index + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn renders_shapes_offscreen() {
+        let Some(mut renderer) = headless(64, 32) else { return };
+        let target = OffscreenTarget::new(&renderer);
+        assert_eq!((target.width, target.height), (64, 32));
+
+        renderer.begin_frame();
+        renderer.draw_rectangle(Vector2::new(2.0, 2.0), 10.0, 10.0, Colors::Red);
+        renderer.draw_circle(Vector2::new(40.0, 16.0), 8.0, 32, Colors::Blue);
+        renderer.end_frame_offscreen(&target);
+
+        let image = target.read_pixels(&renderer).unwrap();
+        assert_eq!((image.width, image.height), (64, 32));
+
+        assert_eq!(pixel(&image, 2, 2), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 11, 11), [255, 0, 0, 255]);
+        assert_ne!(pixel(&image, 12, 5), [255, 0, 0, 255]);
+        assert_ne!(pixel(&image, 5, 12), [255, 0, 0, 255]);
+
+        assert_eq!(pixel(&image, 40, 16), [0, 0, 255, 255]);
+        assert_eq!(pixel(&image, 46, 16), [0, 0, 255, 255]);
+        assert_ne!(pixel(&image, 49, 16), [0, 0, 255, 255]);
+        assert_ne!(pixel(&image, 33, 9), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn texture_size_is_validated() {
+        let Some(mut renderer) = headless(8, 8) else { return };
+
+        assert!(renderer.create_texture(2, 2, &[255; 16]).is_ok());
+        assert!(matches!(renderer.create_texture(2, 2, &[255; 12]), Err(TextureError::InvalidSize { len: 12, .. })));
+        assert!(matches!(renderer.create_texture(0, 2, &[]), Err(TextureError::InvalidSize { .. })));
+
+        let max = renderer.device.limits().max_texture_dimension_2d;
+        let pixels = vec![0; (max as usize + 1) * 4];
+        assert!(matches!(
+            renderer.create_texture(max + 1, 1, &pixels),
+            Err(TextureError::TooLarge { width, max: limit, .. }) if width == max + 1 && limit == max
+        ));
+    }
+
+    #[test]
+    fn higher_layer_is_drawn_on_top_regardless_of_call_order() {
+        let Some(mut renderer) = headless(16, 16) else { return };
+        let target = OffscreenTarget::new(&renderer);
+
+        renderer.begin_frame();
+        renderer.set_layer(1);
+        renderer.draw_rectangle(Vector2::new(0.0, 0.0), 8.0, 8.0, Colors::Red);
+        renderer.set_layer(0);
+        renderer.draw_rectangle(Vector2::new(4.0, 4.0), 8.0, 8.0, Colors::Blue);
+        renderer.end_frame_offscreen(&target);
+
+        let image = target.read_pixels(&renderer).unwrap();
+        assert_eq!(pixel(&image, 6, 6), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 10, 10), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn layers_are_sorted_across_vertex_buffers() {
+        let Some(mut renderer) = headless(16, 16) else { return };
+        let target = OffscreenTarget::new(&renderer);
+
+        // Each circle needs over half a vertex buffer, so the second one starts a new buffer
+        renderer.begin_frame();
+        renderer.set_layer(1);
+        renderer.draw_circle(Vector2::new(6.0, 6.0), 5.0, 40_000, Colors::Red);
+        renderer.set_layer(0);
+        renderer.draw_circle(Vector2::new(10.0, 10.0), 5.0, 40_000, Colors::Blue);
+        renderer.end_frame_offscreen(&target);
+
+        assert_eq!(renderer.stats().vertex_buffers, 2);
+        let image = target.read_pixels(&renderer).unwrap();
+        assert_eq!(pixel(&image, 8, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 12, 12), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn sorting_by_state_merges_batches() {
+        let Some(mut renderer) = headless(16, 16) else { return };
+        let target = OffscreenTarget::new(&renderer);
+        let white = renderer.create_texture(1, 1, &[255; 4]).unwrap();
+        let black = renderer.create_texture(1, 1, &[0, 0, 0, 255]).unwrap();
+
+        let draw = |renderer: &mut Renderer| {
+            renderer.begin_frame();
+            for i in 0..8 {
+                let texture = if i % 2 == 0 { white } else { black };
+                let pos = Vector2::new((i % 4) as f32 * 4.0, (i / 4) as f32 * 4.0);
+                renderer.draw_sprite(texture, pos, Vector2::new(4.0, 4.0), None, Colors::White, 0.0);
+            }
+            renderer.end_frame_offscreen(&target);
+            renderer.stats().batches
+        };
+
+        assert_eq!(draw(&mut renderer), 8);
+        renderer.set_sort_by_state(true);
+        assert_eq!(draw(&mut renderer), 2);
+
+        // Sprites don't overlap, so reordering keeps every one in place
+        let image = target.read_pixels(&renderer).unwrap();
+        assert_eq!(pixel(&image, 1, 1), [255, 255, 255, 255]);
+        assert_eq!(pixel(&image, 5, 1), [0, 0, 0, 255]);
+        assert_eq!(pixel(&image, 1, 5), [255, 255, 255, 255]);
+    }
+}
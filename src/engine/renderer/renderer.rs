@@ -1,19 +1,51 @@
-use wgpu::util::DeviceExt;
+use std::path::Path;
+use crate::engine::error::EngineError;
 use crate::engine::math::vector2::Vector2;
+use crate::engine::math::rect::Rect;
+use crate::engine::math::transform::Transform2D;
 use bytemuck::{Pod, Zeroable};
-use wgpu::StoreOp;
+use wgpu::util::DeviceExt;
+use crate::engine::renderer::blend::BlendMode;
+use crate::engine::renderer::camera::Camera2D;
 use crate::engine::renderer::colors::Colors;
+use crate::engine::renderer::font::{Font, FontData, FontError, TextAlign, TextLayout, DEFAULT_CHARSET};
+use crate::engine::renderer::offscreen::OffscreenTarget;
+use crate::engine::renderer::stroke::{stroke_triangles, StrokeStyle};
+use crate::engine::renderer::tessellate::{fill_triangles, FillRule};
+use crate::engine::renderer::texture::{GpuTexture, Image, Texture, TextureError};
 
 pub struct Renderer{
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
-    pub pipeline: wgpu::RenderPipeline,
     pub surface_config: wgpu::SurfaceConfiguration,
 
     vertex_buffer: wgpu::Buffer,
-    max_vertices: usize,
+    vertex_capacity: usize,
+    index_buffer: wgpu::Buffer,
+    index_capacity: usize,
+    stats: RenderStats,
 
     vertices: Vec<Vertex>,
+    indices: Vec<u16>,
+    chunks: Vec<Chunk>,
+    batches: Vec<Batch>,
+    blend_mode: BlendMode,
+    layer: i32,
+    sort_by_state: bool,
+    transform: Transform2D,
+    transform_stack: Vec<Transform2D>,
+    pipelines: [wgpu::RenderPipeline; 5],
+
+    texture_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    linear_sampler: wgpu::Sampler,
+    textures: Vec<GpuTexture>,
+    white_texture: Texture,
+
+    camera: Camera2D,
+    scale_factor: f32,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
 }
 
 #[repr(C)]
@@ -21,11 +53,49 @@ pub struct Renderer{
 pub struct Vertex {
     pub position: [f32; 2],
     pub color: [f32; 4],
+    pub uv: [f32; 2],
+}
+
+/// Numbers describing the last finished frame
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub vertices: usize,
+    pub indices: usize,
+    /// Draw calls issued, a new one starts whenever the texture, blend mode or vertex buffer changes
+    pub batches: usize,
+    /// Vertex buffers used, a new one starts every `MAX_CHUNK_VERTICES` vertices
+    pub vertex_buffers: usize,
+    /// Vertices that fit in the persistent vertex buffer
+    pub vertex_capacity: usize,
+}
+
+/// Vertices addressable by `u16` indices, frames above that are split into chunks
+pub const MAX_CHUNK_VERTICES: usize = u16::MAX as usize + 1;
+
+/// Two triangles over top-left, bottom-left, top-right, bottom-right
+const QUAD_INDICES: [u16; 6] = [0, 1, 2, 2, 1, 3];
+
+/// Start of a run of vertices and indices uploaded to one vertex buffer.
+/// Indices are relative to `vertex_start`
+#[derive(Debug, Copy, Clone)]
+struct Chunk {
+    vertex_start: usize,
+    index_start: usize,
+}
+
+/// Range of indices drawn with a single texture and blend mode from a single chunk
+#[derive(Debug, Copy, Clone)]
+struct Batch {
+    layer: i32,
+    texture: usize,
+    blend: BlendMode,
+    chunk: usize,
+    start: u32,
+    end: u32,
 }
 
 impl Vertex {
     pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
-        use std::mem;
         wgpu::VertexBufferLayout{
             array_stride: size_of::<Vertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
@@ -39,6 +109,11 @@ impl Vertex {
                     offset: size_of::<[f32; 2]>() as u64,
                     shader_location: 1,
                     format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 6]>() as u64,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x2,
                 }
             ]
         }
@@ -53,7 +128,7 @@ impl Renderer {
         format: wgpu::TextureFormat,
         width: u32,
         height: u32,
-        max_vertices: usize,
+        initial_vertices: usize,
     ) -> Self {
         let surface_config = wgpu::SurfaceConfiguration{
             usage:wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -64,105 +139,568 @@ impl Renderer {
             view_formats: vec![],
         };
 
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Pipeline Layout"),
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
+        let texture_layout = GpuTexture::bind_group_layout(&device);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Texture Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Shape Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                compilation_options: Default::default(),
-                buffers: &[Vertex::desc()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                compilation_options: Default::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
+        let linear_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Linear Texture Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
         });
 
-        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Vertex Buffer"),
-            size: (max_vertices * std::mem::size_of::<Vertex>()) as u64,
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        let camera = Camera2D::new(Vector2::new(width as f32, height as f32));
+
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Camera Buffer"),
+            size: size_of::<[[f32; 4]; 4]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
 
-        Self { device, queue, pipeline, surface_config, vertex_buffer, max_vertices, vertices: Vec::with_capacity(max_vertices) }
+        let camera_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Camera Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Camera Bind Group"),
+            layout: &camera_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pipeline Layout"),
+            bind_group_layouts: &[&texture_layout, &camera_layout],
+            push_constant_ranges: &[],
+        });
+
+        // One pipeline per blend mode, indexed by `BlendMode::index`
+        let pipelines = BlendMode::ALL.map(|mode| Self::create_pipeline(&device, &pipeline_layout, &shader, format, mode));
+
+        let vertex_capacity = initial_vertices.clamp(4, MAX_CHUNK_VERTICES);
+        let vertex_buffer = Self::create_buffer(&device, "Vertex Buffer", wgpu::BufferUsages::VERTEX, vertex_capacity * size_of::<Vertex>());
+
+        // Quads take 6 indices for every 4 vertices
+        let index_capacity = (vertex_capacity * 3 / 2).next_multiple_of(2);
+        let index_buffer = Self::create_buffer(&device, "Index Buffer", wgpu::BufferUsages::INDEX, index_capacity * size_of::<u16>());
+
+        // Shapes are drawn with a 1x1 white texture, so they can share batches with sprites
+        let white = GpuTexture::new(&device, &queue, &texture_layout, &sampler, 1, 1, &[255; 4]);
+        let white_texture = Texture { id: 0, width: 1, height: 1 };
+
+        Self {
+            device, queue, surface_config,
+            vertex_buffer, vertex_capacity, index_buffer, index_capacity,
+            stats: RenderStats::default(),
+            vertices: Vec::with_capacity(vertex_capacity),
+            indices: Vec::with_capacity(index_capacity),
+            chunks: vec![Chunk { vertex_start: 0, index_start: 0 }],
+            batches: Vec::new(),
+            blend_mode: BlendMode::default(),
+            layer: 0,
+            sort_by_state: false,
+            transform: Transform2D::identity(),
+            transform_stack: Vec::new(),
+            pipelines,
+            texture_layout, sampler, linear_sampler,
+            textures: vec![white],
+            white_texture,
+            camera, scale_factor: 1.0, camera_buffer, camera_bind_group,
+        }
+    }
+
+    /// Creates renderer without a window, drawing into `OffscreenTarget`s.
+    /// Works with software adapters such as llvmpipe or lavapipe
+    pub async fn headless(width: u32, height: u32, initial_vertices: usize) -> Result<Self, EngineError> {
+        let instance = wgpu::Instance::default();
+
+        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }).await.ok_or_else(|| EngineError::no_adapter(&instance))?;
+
+        let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+            label: None,
+            required_features: Default::default(),
+            required_limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+        }, None).await?;
+
+        let shader = Self::create_shader(&device);
+
+        Ok(Self::new(device, queue, shader, wgpu::TextureFormat::Rgba8UnormSrgb, width, height, initial_vertices))
+    }
+
+    pub fn create_shader(device: &wgpu::Device) -> wgpu::ShaderModule {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shape Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/shader.wgsl").into()),
+        })
+    }
+
+    /// Uploads RGBA8 pixels as a new texture, sampled without filtering
+    pub fn create_texture(&mut self, width: u32, height: u32, pixels: &[u8]) -> Result<Texture, TextureError> {
+        self.create_texture_filtered(width, height, pixels, wgpu::FilterMode::Nearest)
+    }
+
+    /// Uploads RGBA8 pixels as a new texture. `Linear` filtering suits scaled images and text,
+    /// `Nearest` keeps pixel art sharp. Fails when the size is empty, doesn't match `pixels`
+    /// or exceeds what the GPU supports
+    pub fn create_texture_filtered(&mut self, width: u32, height: u32, pixels: &[u8], filter: wgpu::FilterMode) -> Result<Texture, TextureError> {
+        let expected_len = width as u64 * height as u64 * 4;
+        if width == 0 || height == 0 || pixels.len() as u64 != expected_len {
+            return Err(TextureError::InvalidSize { width, height, len: pixels.len() });
+        }
+        let max = self.max_texture_dimension();
+        if width > max || height > max {
+            return Err(TextureError::TooLarge { width, height, max });
+        }
+
+        let sampler = match filter {
+            wgpu::FilterMode::Nearest => &self.sampler,
+            wgpu::FilterMode::Linear => &self.linear_sampler,
+        };
+        let gpu = GpuTexture::new(&self.device, &self.queue, &self.texture_layout, sampler, width, height, pixels);
+        self.textures.push(gpu);
+
+        Ok(Texture { id: self.textures.len() - 1, width, height })
+    }
+
+    /// Loads texture from PNG file
+    pub fn load_texture(&mut self, path: impl AsRef<Path>) -> Result<Texture, TextureError> {
+        let image = Image::from_png_file(path)?;
+        self.create_texture(image.width, image.height, &image.pixels)
+    }
+
+    /// Loads texture from PNG bytes, e.g. from `include_bytes!`
+    pub fn load_texture_from_bytes(&mut self, bytes: &[u8]) -> Result<Texture, TextureError> {
+        let image = Image::from_png_bytes(bytes)?;
+        self.create_texture(image.width, image.height, &image.pixels)
+    }
+
+    /// Loads TTF/OTF font from file, rasterizing `DEFAULT_CHARSET` at `size` pixels
+    pub fn load_font(&mut self, path: impl AsRef<Path>, size: f32) -> Result<Font, FontError> {
+        let bytes = std::fs::read(path)?;
+        self.load_font_from_bytes(&bytes, size)
+    }
+
+    /// Loads TTF/OTF font from bytes, rasterizing `DEFAULT_CHARSET` at `size` pixels
+    pub fn load_font_from_bytes(&mut self, bytes: &[u8], size: f32) -> Result<Font, FontError> {
+        let data = FontData::from_ttf_bytes(bytes, size, DEFAULT_CHARSET, self.max_texture_dimension())?;
+        self.upload_font(data)
+    }
+
+    /// Loads text BMFont descriptor (`.fnt`) together with its PNG page
+    pub fn load_bmfont(&mut self, path: impl AsRef<Path>) -> Result<Font, FontError> {
+        let data = FontData::from_bmfont_file(path.as_ref())?;
+        self.upload_font(data)
+    }
+
+    /// Loads BMFont from descriptor text and PNG page bytes
+    pub fn load_bmfont_from_bytes(&mut self, descriptor: &str, page: &[u8]) -> Result<Font, FontError> {
+        let data = FontData::from_bmfont(descriptor, page)?;
+        self.upload_font(data)
+    }
+
+    fn upload_font(&mut self, data: FontData) -> Result<Font, FontError> {
+        let (width, height, max) = (data.atlas.width, data.atlas.height, self.max_texture_dimension());
+        if width > max || height > max {
+            return Err(FontError::AtlasTooLarge { width, height, max });
+        }
+
+        let texture = self.create_texture_filtered(width, height, &data.atlas.pixels, wgpu::FilterMode::Linear)?;
+        Ok(Font::new(texture, data))
+    }
+
+    /// Largest width or height of a texture the device supports
+    fn max_texture_dimension(&self) -> u32 {
+        self.device.limits().max_texture_dimension_2d
+    }
+
+    /// Statistics of the last `end_frame`
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Sets blend mode used by all following draw calls, until the next `begin_frame`
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    pub fn layer(&self) -> i32 {
+        self.layer
+    }
+
+    /// Sets layer of all following draw calls, until the next `begin_frame` resets it to 0.
+    /// Higher layers are drawn on top of lower ones, calls on the same layer keep their order
+    pub fn set_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    pub fn sort_by_state(&self) -> bool {
+        self.sort_by_state
+    }
+
+    /// Groups draw calls within a layer by blend mode and texture, so they take fewer batches.
+    /// Calls with different textures then no longer keep their order inside the layer,
+    /// so only enable it when overlapping draws are separated into layers
+    pub fn set_sort_by_state(&mut self, enabled: bool) {
+        self.sort_by_state = enabled;
+    }
+
+    /// Transform applied to all following shapes, sprites and text
+    pub fn transform(&self) -> Transform2D {
+        self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Transform2D) {
+        self.transform = transform;
+    }
+
+    pub fn reset_transform(&mut self) {
+        self.transform = Transform2D::identity();
+    }
+
+    /// Saves the current transform, restore it with `pop_transform`
+    pub fn push_transform(&mut self) {
+        self.transform_stack.push(self.transform);
+    }
+
+    /// Restores the transform saved by the matching `push_transform`
+    pub fn pop_transform(&mut self) {
+        if let Some(transform) = self.transform_stack.pop() {
+            self.transform = transform;
+        }
+    }
+
+    /// Applies `transform` before the current one, so following draws happen in its local space
+    pub fn apply_transform(&mut self, transform: Transform2D) {
+        self.transform = self.transform * transform;
+    }
+
+    pub fn translate(&mut self, offset: Vector2) {
+        self.apply_transform(Transform2D::translation(offset));
+    }
+
+    /// Rotates around the current origin, in radians
+    pub fn rotate(&mut self, angle: f32) {
+        self.apply_transform(Transform2D::rotation(angle));
+    }
+
+    pub fn scale(&mut self, scale: Vector2) {
+        self.apply_transform(Transform2D::scaling(scale));
+    }
+
+    pub fn camera(&self) -> &Camera2D {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera2D {
+        &mut self.camera
+    }
+
+    pub fn set_camera(&mut self, camera: Camera2D) {
+        self.camera = camera;
+    }
+
+    /// Updates output size in physical pixels. The world point under the top-left corner stays in place,
+    /// so with the default camera world units keep matching logical pixels
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.surface_config.width = width;
+        self.surface_config.height = height;
+        self.update_viewport();
+    }
+
+    /// Physical pixels per logical pixel. Drawing happens in logical pixels,
+    /// so the same scene covers the same part of the window on any display
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Changes the scale factor, kept up to date by `App` when the window moves between displays
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+        self.update_viewport();
+    }
+
+    /// Output size in pixels
+    pub fn physical_size(&self) -> (u32, u32) {
+        (self.surface_config.width, self.surface_config.height)
+    }
+
+    /// Output size in logical pixels, the area the default camera shows
+    pub fn logical_size(&self) -> Vector2 {
+        Vector2::new(self.surface_config.width as f32, self.surface_config.height as f32) / self.scale_factor
     }
 
+    fn update_viewport(&mut self) {
+        let anchor = self.camera.screen_to_world(Vector2::zero());
+        self.camera.viewport = self.logical_size();
+        let shift = self.camera.screen_to_world(Vector2::zero()) - anchor;
+        self.camera.position = self.camera.position - shift;
+    }
+
+    /// Starts a new frame, clearing queued geometry and resetting the transform stack,
+    /// layer and blend mode. Sorting by state is a setting and stays as it was
     pub fn begin_frame(&mut self) {
         self.vertices.clear();
+        self.indices.clear();
+        self.chunks.clear();
+        self.chunks.push(Chunk { vertex_start: 0, index_start: 0 });
+        self.batches.clear();
+        self.transform = Transform2D::identity();
+        self.transform_stack.clear();
+        self.layer = 0;
+        self.blend_mode = BlendMode::default();
     }
+
     pub fn draw_rectangle(&mut self, pos: Vector2, width: f32, height: f32, color: Colors) {
-        let window_size = Vector2::new(self.surface_config.width as f32, self.surface_config.height as f32);
         let color = color.as_f32();
+        let uv = [0.0, 0.0];
 
-        let top_left = pos.to_ndc(window_size);
-        let top_right = Vector2::new(pos.x + width, pos.y).to_ndc(window_size);
-        let bottom_left = Vector2::new(pos.x, pos.y + height).to_ndc(window_size);
-        let bottom_right = Vector2::new(pos.x + width, pos.y + height).to_ndc(window_size);
+        let top_left = pos.to_array();
+        let top_right = [pos.x + width, pos.y];
+        let bottom_left = [pos.x, pos.y + height];
+        let bottom_right = [pos.x + width, pos.y + height];
 
-        // Dwie trójkąty tworzące prostokąt
-        self.vertices.extend_from_slice(&[
-            Vertex { position: top_left, color },
-            Vertex { position: bottom_left, color },
-            Vertex { position: top_right, color },
-
-            Vertex { position: top_right, color },
-            Vertex { position: bottom_left, color },
-            Vertex { position: bottom_right, color },
-        ]);
+        self.push_geometry(self.white_texture, &[
+            Vertex { position: top_left, color, uv },
+            Vertex { position: bottom_left, color, uv },
+            Vertex { position: top_right, color, uv },
+            Vertex { position: bottom_right, color, uv },
+        ], &QUAD_INDICES);
     }
 
     pub fn draw_triangle(&mut self, a: Vector2, b: Vector2, c: Vector2, color: Colors) {
-        let window_size = Vector2::new(self.surface_config.width as f32, self.surface_config.height as f32);
         let color = color.as_f32();
+        let uv = [0.0, 0.0];
 
-        self.vertices.extend_from_slice(&[
-            Vertex { position: a.to_ndc(window_size), color },
-            Vertex { position: b.to_ndc(window_size), color },
-            Vertex { position: c.to_ndc(window_size), color },
-        ]);
+        self.push_geometry(self.white_texture, &[
+            Vertex { position: a.to_array(), color, uv },
+            Vertex { position: b.to_array(), color, uv },
+            Vertex { position: c.to_array(), color, uv },
+        ], &[0, 1, 2]);
     }
 
     pub fn draw_circle(&mut self, center: Vector2, radius: f32, segments: usize, color: Colors) {
-        let window_size = Vector2::new(self.surface_config.width as f32, self.surface_config.height as f32);
+        // Center plus rim vertices, one triangle fan sharing all of them
+        let segments = segments.min(MAX_CHUNK_VERTICES - 1);
+        if segments < 3 {
+            return;
+        }
+
         let color = color.as_f32();
+        let uv = [0.0, 0.0];
+
+        let mut vertices = Vec::with_capacity(segments + 1);
+        vertices.push(Vertex { position: center.to_array(), color, uv });
 
         for i in 0..segments {
-            let theta1 = (i as f32 / segments as f32) * std::f32::consts::TAU;
-            let theta2 = ((i + 1) as f32 / segments as f32) * std::f32::consts::TAU;
+            let theta = (i as f32 / segments as f32) * std::f32::consts::TAU;
 
-            let p1 = Vector2 {
-                x: center.x + radius * theta1.cos(),
-                y: center.y + radius * theta1.sin(),
+            let p = Vector2 {
+                x: center.x + radius * theta.cos(),
+                y: center.y + radius * theta.sin(),
             };
 
-            let p2 = Vector2 {
-                x: center.x + radius * theta2.cos(),
-                y: center.y + radius * theta2.sin(),
+            vertices.push(Vertex { position: p.to_array(), color, uv });
+        }
+
+        let indices: Vec<u16> = (0..segments)
+            .flat_map(|i| [0, i as u16 + 1, ((i + 1) % segments) as u16 + 1])
+            .collect();
+
+        self.push_geometry(self.white_texture, &vertices, &indices);
+    }
+
+    /// Fills the polygon through `points`, which may be concave or self-intersecting.
+    /// Self-overlapping parts are filled by the `NonZero` rule
+    pub fn fill_polygon(&mut self, points: &[Vector2], color: Colors) {
+        self.fill_contours(&[points], FillRule::NonZero, color);
+    }
+
+    /// Fills several closed contours together, e.g. an outline and its holes
+    pub fn fill_contours(&mut self, contours: &[&[Vector2]], rule: FillRule, color: Colors) {
+        let triangles = fill_triangles(contours, rule);
+        self.push_triangles(&triangles, color);
+    }
+
+    /// Draws a line `thickness` wide with flat ends
+    pub fn draw_line(&mut self, a: Vector2, b: Vector2, thickness: f32, color: Colors) {
+        self.draw_polyline(&[a, b], StrokeStyle::new(thickness), color);
+    }
+
+    /// Draws connected line segments through `points`
+    pub fn draw_polyline(&mut self, points: &[Vector2], style: StrokeStyle, color: Colors) {
+        let triangles = stroke_triangles(points, false, &style);
+        self.push_triangles(&triangles, color);
+    }
+
+    /// Draws outline of the polygon through `points`, closing it back to the first point. Caps are ignored
+    pub fn stroke_polygon(&mut self, points: &[Vector2], style: StrokeStyle, color: Colors) {
+        let triangles = stroke_triangles(points, true, &style);
+        self.push_triangles(&triangles, color);
+    }
+
+    /// Draws outline of the rectangle, centered on its edges
+    pub fn stroke_rectangle(&mut self, pos: Vector2, width: f32, height: f32, thickness: f32, color: Colors) {
+        let corners = [
+            pos,
+            Vector2::new(pos.x + width, pos.y),
+            Vector2::new(pos.x + width, pos.y + height),
+            Vector2::new(pos.x, pos.y + height),
+        ];
+        self.stroke_polygon(&corners, StrokeStyle::new(thickness), color);
+    }
+
+    /// Draws ring centered on the circle's edge
+    pub fn stroke_circle(&mut self, center: Vector2, radius: f32, segments: usize, thickness: f32, color: Colors) {
+        // Inner and outer rim vertices, one quad per segment
+        let segments = segments.min(MAX_CHUNK_VERTICES / 2);
+        if segments < 3 || thickness <= 0.0 {
+            return;
+        }
+
+        let color = color.as_f32();
+        let uv = [0.0, 0.0];
+        let inner = (radius - thickness / 2.0).max(0.0);
+        let outer = radius + thickness / 2.0;
+
+        let mut vertices = Vec::with_capacity(segments * 2);
+        for i in 0..segments {
+            let (sin, cos) = ((i as f32 / segments as f32) * std::f32::consts::TAU).sin_cos();
+            vertices.push(Vertex { position: [center.x + inner * cos, center.y + inner * sin], color, uv });
+            vertices.push(Vertex { position: [center.x + outer * cos, center.y + outer * sin], color, uv });
+        }
+
+        let indices: Vec<u16> = (0..segments)
+            .flat_map(|i| {
+                let next = (i + 1) % segments;
+                let (a, b, c, d) = (i * 2, i * 2 + 1, next * 2, next * 2 + 1);
+                [a, b, c, c, b, d].map(|index| index as u16)
+            })
+            .collect();
+
+        self.push_geometry(self.white_texture, &vertices, &indices);
+    }
+
+    /// Draws custom mesh with the white texture. Positions are in world space and
+    /// indices point into `vertices`, so a single mesh holds at most `MAX_CHUNK_VERTICES`
+    pub fn draw_indexed(&mut self, vertices: &[Vertex], indices: &[u16]) {
+        self.push_geometry(self.white_texture, vertices, indices);
+    }
+
+    /// Same as `draw_indexed`, sampling `texture` with the vertex UVs
+    pub fn draw_indexed_textured(&mut self, texture: Texture, vertices: &[Vertex], indices: &[u16]) {
+        self.push_geometry(texture, vertices, indices);
+    }
+
+    /// Draws `source_rect` of the texture (whole texture if `None`) stretched to `size`.
+    /// `pos` is the top-left corner and `rotation` (in radians) spins the sprite around its center
+    pub fn draw_sprite(
+        &mut self,
+        texture: Texture,
+        pos: Vector2,
+        size: Vector2,
+        source_rect: Option<Rect>,
+        tint: Colors,
+        rotation: f32,
+    ) {
+        let color = tint.as_f32();
+
+        let source = source_rect.unwrap_or(Rect::new(0.0, 0.0, texture.width as f32, texture.height as f32));
+        let u0 = source.x / texture.width as f32;
+        let v0 = source.y / texture.height as f32;
+        let u1 = (source.x + source.width) / texture.width as f32;
+        let v1 = (source.y + source.height) / texture.height as f32;
+
+        let half = size / 2.0;
+        let center = pos + half;
+        let (sin, cos) = rotation.sin_cos();
+        let corner = |x: f32, y: f32| {
+            [center.x + x * cos - y * sin, center.y + x * sin + y * cos]
+        };
+
+        let top_left = corner(-half.x, -half.y);
+        let top_right = corner(half.x, -half.y);
+        let bottom_left = corner(-half.x, half.y);
+        let bottom_right = corner(half.x, half.y);
+
+        self.push_geometry(texture, &[
+            Vertex { position: top_left, color, uv: [u0, v0] },
+            Vertex { position: bottom_left, color, uv: [u0, v1] },
+            Vertex { position: top_right, color, uv: [u1, v0] },
+            Vertex { position: bottom_right, color, uv: [u1, v1] },
+        ], &QUAD_INDICES);
+    }
+
+    /// Draws text with its top-left corner at `pos`. `size` is in pixels, the atlas is
+    /// scaled when it differs from the size the font was loaded at
+    pub fn draw_text(&mut self, font: &Font, text: &str, pos: Vector2, size: f32, color: Colors) {
+        self.draw_text_ex(font, text, pos, size, color, TextLayout::default());
+    }
+
+    /// Draws text with wrapping and alignment
+    pub fn draw_text_ex(&mut self, font: &Font, text: &str, pos: Vector2, size: f32, color: Colors, layout: TextLayout) {
+        let scale = size / font.size;
+
+        for (i, line) in font.wrap(text, size, layout.max_width).iter().enumerate() {
+            let width = font.line_width(line, size);
+            let x = match (layout.align, layout.max_width) {
+                (TextAlign::Left, _) => pos.x,
+                (TextAlign::Center, Some(max_width)) => pos.x + (max_width - width) / 2.0,
+                (TextAlign::Center, None) => pos.x - width / 2.0,
+                (TextAlign::Right, Some(max_width)) => pos.x + max_width - width,
+                (TextAlign::Right, None) => pos.x - width,
             };
+            let y = pos.y + i as f32 * font.line_height * scale;
+
+            let mut pen = x;
+            let mut prev = None;
+            for c in line.chars() {
+                if let Some(prev) = prev {
+                    pen += font.kerning(prev, c) * scale;
+                }
+                prev = Some(c);
 
-            self.vertices.extend_from_slice(&[
-                Vertex { position: center.to_ndc(window_size), color },
-                Vertex { position: p1.to_ndc(window_size), color },
-                Vertex { position: p2.to_ndc(window_size), color },
-            ]);
+                let Some(glyph) = font.glyph(c) else { continue };
+                if glyph.source.width > 0.0 && glyph.source.height > 0.0 {
+                    let glyph_pos = Vector2::new(pen, y) + glyph.offset * scale;
+                    self.draw_sprite(font.texture, glyph_pos, glyph.source.size() * scale, Some(glyph.source), color, 0.0);
+                }
+                pen += glyph.advance * scale;
+            }
         }
     }
 
@@ -171,12 +709,48 @@ impl Renderer {
         encoder: &mut wgpu::CommandEncoder,
         view: &wgpu::TextureView,
     ) {
-        assert!(self.vertices.len() <= self.max_vertices, "Przekroczono max_vertices!");
+        self.sort_batches();
+
+        let chunk_ranges: Vec<_> = (0..self.chunks.len()).map(|i| self.chunk_range(i)).collect();
+
+        let (first_vertices, first_indices) = &chunk_ranges[0];
+        self.reserve(first_vertices.len(), first_indices.len());
+
+        // First chunk goes to the persistent buffers, the rest get temporary ones
+        let mut overflow_buffers = Vec::new();
+        for (i, (vertex_range, index_range)) in chunk_ranges.iter().enumerate() {
+            let vertices = &self.vertices[vertex_range.clone()];
+            let indices = &self.indices[index_range.clone()];
+
+            if i == 0 {
+                self.queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vertices));
+
+                // Buffer writes must be a multiple of 4 bytes, so an odd last index gets padded
+                let (even, rest) = indices.split_at(indices.len() & !1);
+                self.queue.write_buffer(&self.index_buffer, 0, bytemuck::cast_slice(even));
+                if let [last] = rest {
+                    let offset = size_of_val(even) as u64;
+                    self.queue.write_buffer(&self.index_buffer, offset, bytemuck::cast_slice(&[*last, 0]));
+                }
+            } else {
+                let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Overflow Vertex Buffer"),
+                    contents: bytemuck::cast_slice(vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+                let index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Overflow Index Buffer"),
+                    contents: bytemuck::cast_slice(indices),
+                    usage: wgpu::BufferUsages::INDEX,
+                });
+                overflow_buffers.push((vertex_buffer, index_buffer));
+            }
+        }
 
         self.queue.write_buffer(
-            &self.vertex_buffer,
+            &self.camera_buffer,
             0,
-            bytemuck::cast_slice(&self.vertices),
+            bytemuck::bytes_of(&self.camera.uniform()),
         );
 
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -199,44 +773,208 @@ impl Renderer {
             occlusion_query_set: None,
         });
 
-        render_pass.set_pipeline(&self.pipeline);
-        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        render_pass.draw(0..self.vertices.len() as u32, 0..1);
+        render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
+
+        let mut current_chunk = None;
+        let mut current_blend = None;
+        for batch in &self.batches {
+            if current_blend != Some(batch.blend) {
+                render_pass.set_pipeline(&self.pipelines[batch.blend.index()]);
+                current_blend = Some(batch.blend);
+            }
+
+            if current_chunk != Some(batch.chunk) {
+                let (vertex_buffer, index_buffer) = match batch.chunk {
+                    0 => (&self.vertex_buffer, &self.index_buffer),
+                    i => (&overflow_buffers[i - 1].0, &overflow_buffers[i - 1].1),
+                };
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                current_chunk = Some(batch.chunk);
+            }
+
+            let offset = chunk_ranges[batch.chunk].1.start as u32;
+            render_pass.set_bind_group(0, &self.textures[batch.texture].bind_group, &[]);
+            render_pass.draw_indexed(batch.start - offset..batch.end - offset, 0, 0..1);
+        }
+
+        self.stats = RenderStats {
+            vertices: self.vertices.len(),
+            indices: self.indices.len(),
+            batches: self.batches.len(),
+            vertex_buffers: self.chunks.len(),
+            vertex_capacity: self.vertex_capacity,
+        };
     }
 
-    fn render_vertices(
-        &self,
-        encoder: &mut wgpu::CommandEncoder,
-        view: &wgpu::TextureView,
-        vertices: &[Vertex],
-    ) {
-        let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Render Shape"),
-            contents: bytemuck::cast_slice(vertices),
-            usage: wgpu::BufferUsages::VERTEX,
+    /// Finishes the frame into `target` and submits it right away
+    pub fn end_frame_offscreen(&mut self, target: &OffscreenTarget) {
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Offscreen Encoder"),
         });
 
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render Shape"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Load,
-                    store: StoreOp::Store,
-                }
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
+        self.end_frame(&mut encoder, target.view());
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+        blend: BlendMode,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shape Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(blend.to_blend_state()),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
 
-        render_pass.set_pipeline(&self.pipeline);
-        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-        render_pass.draw(0..vertices.len() as u32, 0..1);
+    fn create_buffer(device: &wgpu::Device, label: &str, usage: wgpu::BufferUsages, size: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: size as u64,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Grows the persistent buffers so they fit the first chunk
+    fn reserve(&mut self, vertices: usize, indices: usize) {
+        if vertices > self.vertex_capacity {
+            self.vertex_capacity = vertices.next_power_of_two().min(MAX_CHUNK_VERTICES);
+            self.vertex_buffer = Self::create_buffer(&self.device, "Vertex Buffer", wgpu::BufferUsages::VERTEX, self.vertex_capacity * size_of::<Vertex>());
+        }
+
+        if indices > self.index_capacity {
+            self.index_capacity = indices.next_multiple_of(2).next_power_of_two();
+            self.index_buffer = Self::create_buffer(&self.device, "Index Buffer", wgpu::BufferUsages::INDEX, self.index_capacity * size_of::<u16>());
+        }
+    }
+
+    /// Vertex and index ranges belonging to chunk `i`
+    fn chunk_range(&self, i: usize) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let chunk = self.chunks[i];
+        let (vertex_end, index_end) = match self.chunks.get(i + 1) {
+            Some(next) => (next.vertex_start, next.index_start),
+            None => (self.vertices.len(), self.indices.len()),
+        };
+
+        (chunk.vertex_start..vertex_end, chunk.index_start..index_end)
+    }
+
+    /// Appends a mesh, starting a new chunk when it would overflow `u16` indices
+    /// and extending the last batch when it uses the same texture, blend mode and chunk
+    fn push_geometry(&mut self, texture: Texture, vertices: &[Vertex], indices: &[u16]) {
+        if vertices.is_empty() || indices.is_empty() || vertices.len() > MAX_CHUNK_VERTICES {
+            return;
+        }
+        debug_assert!(indices.iter().all(|&i| (i as usize) < vertices.len()), "Index out of mesh bounds");
+
+        let mut chunk = *self.chunks.last().unwrap();
+        if self.vertices.len() - chunk.vertex_start + vertices.len() > MAX_CHUNK_VERTICES {
+            chunk = Chunk { vertex_start: self.vertices.len(), index_start: self.indices.len() };
+            self.chunks.push(chunk);
+        }
+        let chunk_index = self.chunks.len() - 1;
+
+        let base = (self.vertices.len() - chunk.vertex_start) as u16;
+        let start = self.indices.len() as u32;
+        let first_vertex = self.vertices.len();
+        self.vertices.extend_from_slice(vertices);
+        if !self.transform.is_identity() {
+            for vertex in &mut self.vertices[first_vertex..] {
+                let [x, y] = vertex.position;
+                vertex.position = self.transform.transform_point(Vector2::new(x, y)).to_array();
+            }
+        }
+        self.indices.extend(indices.iter().map(|&i| base + i));
+        let end = self.indices.len() as u32;
+
+        match self.batches.last_mut() {
+            Some(batch) if batch.layer == self.layer && batch.texture == texture.id && batch.blend == self.blend_mode && batch.chunk == chunk_index => {
+                batch.end = end
+            }
+            _ => self.batches.push(Batch { layer: self.layer, texture: texture.id, blend: self.blend_mode, chunk: chunk_index, start, end }),
+        }
     }
 
-    fn get_window_size(&self) -> Vector2 {
-        Vector2::new(self.surface_config.width as f32, self.surface_config.height as f32)
+    /// Pushes unindexed triangles with the white texture, split so every mesh fits `u16` indices
+    fn push_triangles(&mut self, triangles: &[[Vector2; 3]], color: Colors) {
+        let color = color.as_f32();
+        let uv = [0.0, 0.0];
+
+        for part in triangles.chunks(MAX_CHUNK_VERTICES / 3) {
+            let vertices: Vec<Vertex> = part
+                .iter()
+                .flatten()
+                .map(|point| Vertex { position: point.to_array(), color, uv })
+                .collect();
+            let indices: Vec<u16> = (0..vertices.len() as u16).collect();
+            self.push_geometry(self.white_texture, &vertices, &indices);
+        }
     }
-}
\ No newline at end of file
+
+    /// Stably orders batches by layer, and by blend mode and texture within a layer when `sort_by_state` is on.
+    /// Indices of every chunk are rewritten in the new order, so neighbouring batches with the same state merge
+    fn sort_batches(&mut self) {
+        let sort_by_state = self.sort_by_state;
+        let key = |batch: &Batch| {
+            if sort_by_state {
+                (batch.layer, batch.blend.index(), batch.texture, batch.chunk)
+            } else {
+                (batch.layer, 0, 0, 0)
+            }
+        };
+        if self.batches.is_sorted_by_key(key) {
+            return;
+        }
+
+        let mut sorted = std::mem::take(&mut self.batches);
+        sorted.sort_by_key(key);
+
+        // Lay out each chunk's indices in draw order, chunks keep their index ranges
+        let mut indices = Vec::with_capacity(self.indices.len());
+        let mut ranges = vec![0..0; sorted.len()];
+        for chunk in 0..self.chunks.len() {
+            for (i, batch) in sorted.iter().enumerate().filter(|(_, batch)| batch.chunk == chunk) {
+                let start = indices.len() as u32;
+                indices.extend_from_slice(&self.indices[batch.start as usize..batch.end as usize]);
+                ranges[i] = start..indices.len() as u32;
+            }
+        }
+        self.indices = indices;
+
+        for (mut batch, range) in sorted.into_iter().zip(ranges) {
+            batch.start = range.start;
+            batch.end = range.end;
+
+            match self.batches.last_mut() {
+                Some(last) if last.texture == batch.texture && last.blend == batch.blend && last.chunk == batch.chunk && last.end == batch.start => {
+                    last.end = batch.end
+                }
+                _ => self.batches.push(batch),
+            }
+        }
+    }
+}
@@ -1,20 +1,35 @@
 struct VertexOut {
     @builtin(position) position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
 }
 
+@group(0) @binding(0)
+var t_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var s_texture: sampler;
+
+struct Camera {
+    view_proj: mat4x4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
 @vertex
 fn vs_main(
     @location(0) pos: vec2<f32>,
-    @location(1) color: vec4<f32>
+    @location(1) color: vec4<f32>,
+    @location(2) uv: vec2<f32>
 ) -> VertexOut {
     var out: VertexOut;
-    out.position = vec4<f32>(pos, 0.0, 1.0);
+    out.position = camera.view_proj * vec4<f32>(pos, 0.0, 1.0);
     out.color = color;
+    out.uv = uv;
     return out;
 }
 
 @fragment
-fn fs_main(@location(0) in_color: vec4<f32>) -> @location(0) vec4<f32> {
-    return in_color;
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    return textureSample(t_texture, s_texture, in.uv) * in.color;
 }
@@ -0,0 +1,248 @@
+use std::f32::consts::PI;
+use crate::engine::math::vector2::Vector2;
+
+/// How two segments of a polyline are connected
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Sharp corner, replaced by `Bevel` when longer than `StrokeStyle::miter_limit`
+    #[default]
+    Miter,
+    /// Corner cut off flat
+    Bevel,
+    Round,
+}
+
+/// How the ends of an open polyline look
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// Ends exactly at the end point
+    #[default]
+    Butt,
+    /// Extends past the end point by half the thickness
+    Square,
+    Round,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub thickness: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest allowed miter as a multiple of `thickness`, same as SVG `stroke-miterlimit`
+    pub miter_limit: f32,
+}
+
+impl StrokeStyle {
+    pub fn new(thickness: f32) -> Self {
+        Self { thickness, join: LineJoin::Miter, cap: LineCap::Butt, miter_limit: 4.0 }
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+}
+
+/// Tessellates a polyline into triangles. Each segment is a quad and joins fill the gap
+/// on the outer side of the corner, so translucent strokes overlap on sharp inner corners
+pub(crate) fn stroke_triangles(points: &[Vector2], closed: bool, style: &StrokeStyle) -> Vec<[Vector2; 3]> {
+    let half = style.thickness / 2.0;
+    let mut points: Vec<Vector2> = points.to_vec();
+    points.dedup_by(|b, a| (*b - *a).length() < 1e-4);
+    if closed && points.len() > 2 && (points[0] - points[points.len() - 1]).length() < 1e-4 {
+        points.pop();
+    }
+
+    let mut triangles = Vec::new();
+    if half <= 0.0 || points.len() < 2 {
+        return triangles;
+    }
+
+    let closed = closed && points.len() > 2;
+    let count = points.len();
+    let segments = if closed { count } else { count - 1 };
+    let direction = |i: usize| (points[(i + 1) % count] - points[i]).normalize();
+
+    for i in 0..segments {
+        let dir = direction(i);
+        let offset = normal(dir) * half;
+        let mut a = points[i];
+        let mut b = points[(i + 1) % count];
+
+        if !closed && style.cap == LineCap::Square {
+            if i == 0 {
+                a = a - dir * half;
+            }
+            if i == segments - 1 {
+                b = b + dir * half;
+            }
+        }
+
+        triangles.push([a + offset, a - offset, b + offset]);
+        triangles.push([b + offset, a - offset, b - offset]);
+    }
+
+    let joints = if closed { 0..count } else { 1..count - 1 };
+    for i in joints {
+        let before = direction((i + count - 1) % count);
+        let after = direction(i);
+        push_join(&mut triangles, points[i], before, after, half, style);
+    }
+
+    if !closed && style.cap == LineCap::Round {
+        let start = direction(0);
+        let end = direction(segments - 1);
+        push_arc(&mut triangles, points[0], normal(start) * half, PI);
+        push_arc(&mut triangles, points[count - 1], normal(end) * -half, PI);
+    }
+
+    triangles
+}
+
+/// Left-hand perpendicular of a unit direction
+fn normal(dir: Vector2) -> Vector2 {
+    Vector2::new(-dir.y, dir.x)
+}
+
+fn cross(a: Vector2, b: Vector2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn push_join(triangles: &mut Vec<[Vector2; 3]>, point: Vector2, before: Vector2, after: Vector2, half: f32, style: &StrokeStyle) {
+    let turn = cross(before, after);
+    if turn.abs() < 1e-6 && before.dot(after) > 0.0 {
+        return;
+    }
+
+    // Gap to fill is on the side the polyline turns away from
+    let side = if turn > 0.0 { -1.0 } else { 1.0 };
+    let outer_before = normal(before) * (half * side);
+    let outer_after = normal(after) * (half * side);
+
+    match style.join {
+        LineJoin::Round => {
+            let sweep = cross(outer_before, outer_after).atan2(outer_before.dot(outer_after));
+            push_arc(triangles, point, outer_before, sweep);
+        }
+        LineJoin::Miter | LineJoin::Bevel => {
+            let bisector = (outer_before + outer_after).normalize();
+            let cos_half = bisector.dot(outer_before) / half;
+
+            if style.join == LineJoin::Miter && cos_half > 1e-6 && 1.0 / cos_half <= style.miter_limit {
+                let tip = point + bisector * (half / cos_half);
+                triangles.push([point, point + outer_before, tip]);
+                triangles.push([point, tip, point + outer_after]);
+            } else {
+                triangles.push([point, point + outer_before, point + outer_after]);
+            }
+        }
+    }
+}
+
+/// Fan around `center` starting at `center + from` and turning by `sweep` radians
+fn push_arc(triangles: &mut Vec<[Vector2; 3]>, center: Vector2, from: Vector2, sweep: f32) {
+    let radius = from.length();
+    let steps = ((sweep.abs() * radius.sqrt() * 2.0).ceil() as usize).clamp(1, 64);
+
+    let mut previous = center + from;
+    for step in 1..=steps {
+        let (sin, cos) = (sweep * step as f32 / steps as f32).sin_cos();
+        let next = center + Vector2::new(from.x * cos - from.y * sin, from.x * sin + from.y * cos);
+        triangles.push([center, previous, next]);
+        previous = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coords: &[(f32, f32)]) -> Vec<Vector2> {
+        coords.iter().map(|&(x, y)| Vector2::new(x, y)).collect()
+    }
+
+    fn area(triangles: &[[Vector2; 3]]) -> f32 {
+        triangles.iter().map(|&[a, b, c]| cross(b - a, c - a).abs() / 2.0).sum()
+    }
+
+    fn stroke_area(coords: &[(f32, f32)], closed: bool, style: StrokeStyle) -> f32 {
+        area(&stroke_triangles(&points(coords), closed, &style))
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "expected {expected}, got {actual}");
+    }
+
+    const CORNER: &[(f32, f32)] = &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)];
+
+    #[test]
+    fn butt_line_covers_length_times_thickness() {
+        assert_close(stroke_area(&[(0.0, 0.0), (10.0, 0.0)], false, StrokeStyle::new(2.0)), 20.0);
+        assert_close(stroke_area(&[(1.0, 1.0), (4.0, 5.0)], false, StrokeStyle::new(3.0)), 15.0);
+    }
+
+    #[test]
+    fn square_cap_adds_thickness_squared() {
+        let style = StrokeStyle::new(2.0).cap(LineCap::Square);
+        assert_close(stroke_area(&[(0.0, 0.0), (10.0, 0.0)], false, style), 24.0);
+    }
+
+    #[test]
+    fn round_cap_adds_a_circle() {
+        let style = StrokeStyle::new(2.0).cap(LineCap::Round);
+        let area = stroke_area(&[(0.0, 0.0), (10.0, 0.0)], false, style);
+        // The arcs are polygons inscribed in the circle, slightly smaller than it
+        assert!(area > 20.0 + 3.0 && area <= 20.0 + PI, "{area}");
+    }
+
+    #[test]
+    fn joins_fill_the_outer_corner() {
+        let miter = stroke_triangles(&points(CORNER), false, &StrokeStyle::new(2.0));
+        assert_close(area(&miter), 41.0);
+        // With y down the corner turns clockwise, so the gap is at the top right
+        assert!(miter.iter().flatten().any(|p| (*p - Vector2::new(11.0, -1.0)).length() < 1e-4));
+
+        assert_close(stroke_area(CORNER, false, StrokeStyle::new(2.0).join(LineJoin::Bevel)), 40.5);
+        let round = stroke_area(CORNER, false, StrokeStyle::new(2.0).join(LineJoin::Round));
+        assert!((round - (40.0 + PI / 4.0)).abs() < 0.05, "{round}");
+    }
+
+    #[test]
+    fn miter_over_the_limit_falls_back_to_bevel() {
+        let sharp = &[(0.0, 0.0), (10.0, 0.0), (0.0, 1.0)];
+        let bevel = stroke_area(sharp, false, StrokeStyle::new(2.0).join(LineJoin::Bevel));
+
+        assert!(stroke_area(sharp, false, StrokeStyle::new(2.0).miter_limit(100.0)) > bevel + 1.0);
+        assert_close(stroke_area(sharp, false, StrokeStyle::new(2.0)), bevel);
+    }
+
+    #[test]
+    fn reversal_has_no_nan_vertices() {
+        for join in [LineJoin::Miter, LineJoin::Bevel, LineJoin::Round] {
+            let style = StrokeStyle::new(2.0).join(join).cap(LineCap::Round);
+            let triangles = stroke_triangles(&points(&[(0.0, 0.0), (10.0, 0.0), (5.0, 0.0)]), false, &style);
+            assert!(!triangles.is_empty());
+            assert!(triangles.iter().flatten().all(|p| p.x.is_finite() && p.y.is_finite()), "{join:?}");
+        }
+    }
+
+    #[test]
+    fn degenerate_input_is_empty() {
+        let line = points(&[(0.0, 0.0), (10.0, 0.0)]);
+        assert!(stroke_triangles(&line, false, &StrokeStyle::new(0.0)).is_empty());
+        assert!(stroke_triangles(&line, false, &StrokeStyle::new(-1.0)).is_empty());
+        assert!(stroke_triangles(&points(&[(1.0, 1.0)]), true, &StrokeStyle::new(2.0)).is_empty());
+        assert!(stroke_triangles(&points(&[(1.0, 1.0), (1.0, 1.0)]), false, &StrokeStyle::new(2.0)).is_empty());
+        assert!(stroke_triangles(&[], false, &StrokeStyle::new(2.0)).is_empty());
+    }
+}
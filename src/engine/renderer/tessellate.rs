@@ -0,0 +1,239 @@
+use crate::engine::math::vector2::Vector2;
+
+/// Decides which parts of overlapping or self-intersecting contours are filled
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FillRule {
+    /// Filled where contours wind around a point at least once in total.
+    /// Holes must wind the opposite way to the outline
+    #[default]
+    NonZero,
+    /// Filled where a ray from the point crosses an odd number of edges.
+    /// Holes work with any winding
+    EvenOdd,
+}
+
+impl FillRule {
+    fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// Non-horizontal edge stored top to bottom
+#[derive(Copy, Clone)]
+struct Edge {
+    top: Vector2,
+    bottom: Vector2,
+    /// +1 when the contour goes down along this edge, -1 when it goes up
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        let t = ((y - self.top.y) / (self.bottom.y - self.top.y)).clamp(0.0, 1.0);
+        self.top.x + (self.bottom.x - self.top.x) * t
+    }
+
+    /// Change of x per unit of y
+    fn slope(&self) -> f32 {
+        (self.bottom.x - self.top.x) / (self.bottom.y - self.top.y)
+    }
+}
+
+const EPSILON: f32 = 1e-5;
+
+/// Tessellates filled contours with a scanline sweep. The plane is cut into horizontal
+/// slabs at every vertex and edge crossing, so edges never cross inside a slab and the
+/// filled spans between them become trapezoids. Handles concave shapes, self intersections and holes.
+///
+/// Edges enter an active list sorted by x when the sweep reaches their top and leave it at
+/// their bottom. Only neighbours in that list can cross next, so each slab costs time
+/// proportional to the edges it spans instead of all edges
+pub(crate) fn fill_triangles(contours: &[&[Vector2]], rule: FillRule) -> Vec<[Vector2; 3]> {
+    let mut edges = Vec::new();
+    for contour in contours {
+        for (i, &a) in contour.iter().enumerate() {
+            let b = contour[(i + 1) % contour.len()];
+            if (a.y - b.y).abs() <= EPSILON {
+                continue;
+            }
+
+            if a.y < b.y {
+                edges.push(Edge { top: a, bottom: b, winding: 1 });
+            } else {
+                edges.push(Edge { top: b, bottom: a, winding: -1 });
+            }
+        }
+    }
+    edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+
+    let mut triangles = Vec::new();
+    let mut active: Vec<&Edge> = Vec::new();
+    let mut next_edge = 0;
+    let Some(first) = edges.first() else { return triangles };
+    let mut y0 = first.top.y;
+
+    loop {
+        active.retain(|edge| edge.bottom.y - y0 > EPSILON);
+        while let Some(edge) = edges.get(next_edge).filter(|edge| edge.top.y - y0 <= EPSILON) {
+            active.push(edge);
+            next_edge += 1;
+        }
+        if active.is_empty() && next_edge == edges.len() {
+            break;
+        }
+
+        // Edges barely move between slabs, so insertion sort is close to linear
+        for i in 1..active.len() {
+            let mut j = i;
+            while j > 0 && is_left_below(active[j], active[j - 1], y0) {
+                active.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+
+        let mut y1 = edges.get(next_edge).map_or(f32::INFINITY, |edge| edge.top.y);
+        for edge in &active {
+            y1 = y1.min(edge.bottom.y);
+        }
+        for pair in active.windows(2) {
+            if let Some(y) = crossing_y(pair[0], pair[1]).filter(|&y| y - y0 > EPSILON) {
+                y1 = y1.min(y);
+            }
+        }
+
+        let mut winding = 0;
+        let mut left: Option<&Edge> = None;
+        for &edge in &active {
+            let was_inside = rule.is_inside(winding);
+            winding += edge.winding;
+
+            match (was_inside, rule.is_inside(winding)) {
+                (false, true) => left = Some(edge),
+                (true, false) => {
+                    if let Some(left) = left.take() {
+                        let left_top = Vector2::new(left.x_at(y0), y0);
+                        let left_bottom = Vector2::new(left.x_at(y1), y1);
+                        let right_top = Vector2::new(edge.x_at(y0), y0);
+                        let right_bottom = Vector2::new(edge.x_at(y1), y1);
+                        triangles.push([left_top, left_bottom, right_top]);
+                        triangles.push([right_top, left_bottom, right_bottom]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        y0 = y1;
+    }
+
+    triangles
+}
+
+/// Whether `a` is left of `b` just below `y`. Edges meeting at `y` are ordered by where they go next
+fn is_left_below(a: &Edge, b: &Edge, y: f32) -> bool {
+    let (xa, xb) = (a.x_at(y), b.x_at(y));
+    if (xa - xb).abs() > EPSILON {
+        return xa < xb;
+    }
+    a.slope() < b.slope()
+}
+
+/// Height where two edges cross, if they cross strictly between their ends
+fn crossing_y(a: &Edge, b: &Edge) -> Option<f32> {
+    let top = a.top.y.max(b.top.y);
+    let bottom = a.bottom.y.min(b.bottom.y);
+    if bottom - top <= EPSILON {
+        return None;
+    }
+
+    // Horizontal distance between the edges changes linearly with y
+    let start = a.x_at(top) - b.x_at(top);
+    let end = a.x_at(bottom) - b.x_at(bottom);
+    if start.signum() == end.signum() || start == 0.0 || end == 0.0 {
+        return None;
+    }
+
+    Some(top + (bottom - top) * start / (start - end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coords: &[(f32, f32)]) -> Vec<Vector2> {
+        coords.iter().map(|&(x, y)| Vector2::new(x, y)).collect()
+    }
+
+    fn filled_area(contours: &[&Vec<Vector2>], rule: FillRule) -> f32 {
+        let contours: Vec<&[Vector2]> = contours.iter().map(|contour| contour.as_slice()).collect();
+        fill_triangles(&contours, rule)
+            .iter()
+            .map(|[a, b, c]| ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs() / 2.0)
+            .sum()
+    }
+
+    fn assert_area(contours: &[&Vec<Vector2>], rule: FillRule, expected: f32) {
+        let area = filled_area(contours, rule);
+        assert!((area - expected).abs() < 1e-3, "{rule:?}: expected area {expected}, got {area}");
+    }
+
+    #[test]
+    fn concave_polygon() {
+        let arrow = points(&[(0.0, 1.0), (3.0, 1.0), (3.0, 0.0), (5.0, 2.0), (3.0, 4.0), (3.0, 3.0), (0.0, 3.0)]);
+        assert_area(&[&arrow], FillRule::NonZero, 10.0);
+        assert_area(&[&arrow], FillRule::EvenOdd, 10.0);
+    }
+
+    #[test]
+    fn self_intersecting_bow_tie() {
+        let bow_tie = points(&[(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)]);
+        assert_area(&[&bow_tie], FillRule::NonZero, 2.0);
+        assert_area(&[&bow_tie], FillRule::EvenOdd, 2.0);
+    }
+
+    #[test]
+    fn pentagram_center_depends_on_rule() {
+        let star: Vec<Vector2> = (0..5)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * (i * 2) as f32 / 5.0;
+                Vector2::new(angle.sin() * 10.0, -angle.cos() * 10.0)
+            })
+            .collect();
+        let non_zero = filled_area(&[&star], FillRule::NonZero);
+        let even_odd = filled_area(&[&star], FillRule::EvenOdd);
+
+        // Inner pentagon has circumradius r * cos(72°) / cos(36°)
+        let inner_radius = 10.0 * (72f32.to_radians().cos() / 36f32.to_radians().cos());
+        let pentagon = 2.5 * inner_radius * inner_radius * 72f32.to_radians().sin();
+        assert!((non_zero - even_odd - pentagon).abs() < 1e-2, "{non_zero} - {even_odd} != {pentagon}");
+    }
+
+    #[test]
+    fn overlapping_contours_with_the_same_winding() {
+        let a = points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        let b = points(&[(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)]);
+        assert_area(&[&a, &b], FillRule::NonZero, 7.0);
+        assert_area(&[&a, &b], FillRule::EvenOdd, 6.0);
+    }
+
+    #[test]
+    fn holes() {
+        let outer = points(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+        let opposite = points(&[(1.0, 1.0), (1.0, 3.0), (3.0, 3.0), (3.0, 1.0)]);
+        let same = points(&[(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)]);
+
+        assert_area(&[&outer, &opposite], FillRule::NonZero, 12.0);
+        assert_area(&[&outer, &opposite], FillRule::EvenOdd, 12.0);
+        assert_area(&[&outer, &same], FillRule::NonZero, 16.0);
+        assert_area(&[&outer, &same], FillRule::EvenOdd, 12.0);
+    }
+
+    #[test]
+    fn degenerate_input_is_empty() {
+        assert_area(&[], FillRule::NonZero, 0.0);
+        assert_area(&[&points(&[(0.0, 0.0), (4.0, 0.0)])], FillRule::NonZero, 0.0);
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt;
+use std::path::Path;
+
+/// Handle to a texture owned by the `Renderer`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Texture {
+    pub(crate) id: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    Io(std::io::Error),
+    Decode(png::DecodingError),
+    Encode(png::EncodingError),
+    /// Rendered pixels couldn't be copied back from the GPU
+    Readback(wgpu::BufferAsyncError),
+    /// Larger than the largest texture the GPU supports
+    TooLarge { width: u32, height: u32, max: u32 },
+    /// A side is 0 or the pixel data isn't `width * height * 4` bytes long
+    InvalidSize { width: u32, height: u32, len: usize },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Io(e) => write!(f, "failed to read texture: {e}"),
+            TextureError::Decode(e) => write!(f, "failed to decode PNG: {e}"),
+            TextureError::Encode(e) => write!(f, "failed to encode PNG: {e}"),
+            TextureError::Readback(e) => write!(f, "failed to read texture back from the GPU: {e}"),
+            TextureError::TooLarge { width, height, max } => {
+                write!(f, "texture of {width}x{height} exceeds the maximum texture size {max}")
+            }
+            TextureError::InvalidSize { width, height, len } => {
+                write!(f, "{len} bytes of pixel data don't match a {width}x{height} RGBA8 texture")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+impl From<std::io::Error> for TextureError {
+    fn from(e: std::io::Error) -> Self {
+        TextureError::Io(e)
+    }
+}
+
+impl From<png::DecodingError> for TextureError {
+    fn from(e: png::DecodingError) -> Self {
+        TextureError::Decode(e)
+    }
+}
+
+impl From<png::EncodingError> for TextureError {
+    fn from(e: png::EncodingError) -> Self {
+        TextureError::Encode(e)
+    }
+}
+
+impl From<wgpu::BufferAsyncError> for TextureError {
+    fn from(e: wgpu::BufferAsyncError) -> Self {
+        TextureError::Readback(e)
+    }
+}
+
+/// Decoded image in RGBA8 format
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Decodes PNG bytes, converting any color type to RGBA8
+    pub fn from_png_bytes(bytes: &[u8]) -> Result<Self, TextureError> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer
+                .iter()
+                .flat_map(|&g| [g, g, g, 255])
+                .collect(),
+            // Indexed images are expanded to RGB(A) by `normalize_to_color8`
+            png::ColorType::Indexed => unreachable!(),
+        };
+
+        Ok(Self { width: info.width, height: info.height, pixels })
+    }
+
+    /// Reads and decodes PNG file
+    pub fn from_png_file(path: impl AsRef<Path>) -> Result<Self, TextureError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_png_bytes(&bytes)
+    }
+
+    /// Encodes image as RGBA8 PNG
+    pub fn to_png_bytes(&self) -> Result<Vec<u8>, TextureError> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels)?;
+        }
+        Ok(bytes)
+    }
+
+    /// Writes image to PNG file
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), TextureError> {
+        std::fs::write(path, self.to_png_bytes()?)?;
+        Ok(())
+    }
+}
+
+/// GPU side of a texture, kept inside the `Renderer`
+pub(crate) struct GpuTexture {
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl GpuTexture {
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Texture Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Self {
+        let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Texture Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        Self { bind_group }
+    }
+}
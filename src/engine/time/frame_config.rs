@@ -0,0 +1,61 @@
+/// Presentation mode requested from the surface
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PresentMode {
+    /// Vsync, frames wait for the display. Supported everywhere
+    #[default]
+    Fifo,
+    /// Vsync without blocking, newer frames replace queued ones. Falls back to `Fifo`
+    Mailbox,
+    /// No vsync, may tear. Falls back to `Mailbox`, then `Fifo`
+    Immediate,
+}
+
+impl PresentMode {
+    pub(crate) fn to_wgpu(self) -> wgpu::PresentMode {
+        match self {
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+
+    fn fallback(self) -> Option<Self> {
+        match self {
+            PresentMode::Fifo => None,
+            PresentMode::Mailbox => Some(PresentMode::Fifo),
+            PresentMode::Immediate => Some(PresentMode::Mailbox),
+        }
+    }
+
+    /// Picks this mode or the closest one in `supported`
+    pub(crate) fn select(self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        let mut mode = self;
+        while !supported.contains(&mode.to_wgpu()) {
+            match mode.fallback() {
+                Some(fallback) => mode = fallback,
+                None => break,
+            }
+        }
+        mode.to_wgpu()
+    }
+}
+
+/// When `App` draws a new frame
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RedrawMode {
+    /// Draw frames back to back, limited by vsync and `FrameConfig::target_fps`
+    #[default]
+    Continuous,
+    /// Sleep until a window or input event arrives, then draw a single frame.
+    /// Meant for tools and editors that don't animate on their own
+    OnDemand,
+}
+
+/// Frame pacing settings, provided by `Game::frame_config`
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameConfig {
+    pub present_mode: PresentMode,
+    /// Frames per second to wait for between frames, `None` for no limit
+    pub target_fps: Option<f32>,
+    pub redraw_mode: RedrawMode,
+}
@@ -0,0 +1,2 @@
+pub mod frame_config;
+pub mod time_step;
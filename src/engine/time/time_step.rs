@@ -0,0 +1,124 @@
+/// How `App` advances the game between frames
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum TimeStep {
+    /// One `update` per frame with the measured frame time
+    #[default]
+    Variable,
+    /// `update` runs `tick_rate` times per second with a constant delta time,
+    /// as many times as needed to catch up with real time, but at most `max_steps` per frame.
+    /// Time beyond that is dropped, so a slow frame can't cause ever longer frames
+    Fixed { tick_rate: f32, max_steps: u32 },
+}
+
+impl TimeStep {
+    /// Fixed step at `tick_rate` updates per second, catching up at most 5 steps per frame.
+    /// Panics unless `tick_rate` is positive
+    pub fn fixed(tick_rate: f32) -> Self {
+        assert!(tick_rate > 0.0 && tick_rate.is_finite(), "tick rate must be positive, got {tick_rate}");
+        TimeStep::Fixed { tick_rate, max_steps: 5 }
+    }
+}
+
+/// Splits measured frame time into updates
+#[derive(Default)]
+pub(crate) struct StepTimer {
+    pub time_step: TimeStep,
+    accumulator: f32,
+}
+
+impl StepTimer {
+    pub fn new(time_step: TimeStep) -> Self {
+        Self { time_step, accumulator: 0.0 }
+    }
+
+    /// Drops accumulated time, the next fixed step starts counting from zero
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+
+    /// Returns the delta time and count of updates to run this frame,
+    /// and the interpolation alpha for `draw`. A fixed step with a tick rate
+    /// that isn't positive would never update, so it runs as `Variable`
+    pub fn advance(&mut self, frame_time: f32) -> (f32, u32, f32) {
+        match self.time_step {
+            TimeStep::Variable => (frame_time, 1, 1.0),
+            TimeStep::Fixed { tick_rate, .. } if !(tick_rate > 0.0 && tick_rate.is_finite()) => (frame_time, 1, 1.0),
+            TimeStep::Fixed { tick_rate, max_steps } => {
+                let tick = 1.0 / tick_rate;
+                self.accumulator += frame_time;
+
+                let steps = ((self.accumulator / tick) as u32).min(max_steps);
+                self.accumulator -= steps as f32 * tick;
+                if steps == max_steps {
+                    self.accumulator = self.accumulator.min(tick);
+                }
+
+                (tick, steps, (self.accumulator / tick).clamp(0.0, 1.0))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variable_step_runs_one_update_with_frame_time() {
+        let mut timer = StepTimer::new(TimeStep::Variable);
+        assert_eq!(timer.advance(0.25), (0.25, 1, 1.0));
+    }
+
+    #[test]
+    fn short_frames_run_no_update_until_a_tick_accumulates() {
+        let mut timer = StepTimer::new(TimeStep::fixed(10.0));
+
+        let (step, steps, alpha) = timer.advance(0.04);
+        assert_eq!(steps, 0);
+        assert!((step - 0.1).abs() < 1e-6);
+        assert!((alpha - 0.4).abs() < 1e-5);
+
+        let (_, steps, alpha) = timer.advance(0.04);
+        assert_eq!(steps, 0);
+        assert!((alpha - 0.8).abs() < 1e-5);
+
+        let (_, steps, alpha) = timer.advance(0.04);
+        assert_eq!(steps, 1);
+        assert!((alpha - 0.2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn long_frames_are_clamped_to_max_steps() {
+        let mut timer = StepTimer::new(TimeStep::Fixed { tick_rate: 10.0, max_steps: 3 });
+
+        let (_, steps, alpha) = timer.advance(1.0);
+        assert_eq!(steps, 3);
+        assert!(alpha <= 1.0);
+
+        // Dropped time doesn't make the next frame catch up
+        let (_, steps, _) = timer.advance(0.05);
+        assert!(steps <= 1);
+    }
+
+    #[test]
+    fn invalid_tick_rate_runs_as_variable() {
+        for tick_rate in [0.0, -30.0, f32::NAN, f32::INFINITY] {
+            let mut timer = StepTimer::new(TimeStep::Fixed { tick_rate, max_steps: 5 });
+            assert_eq!(timer.advance(0.25), (0.25, 1, 1.0), "{tick_rate}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "tick rate must be positive")]
+    fn fixed_rejects_zero_tick_rate() {
+        TimeStep::fixed(0.0);
+    }
+
+    #[test]
+    fn reset_drops_accumulated_time() {
+        let mut timer = StepTimer::new(TimeStep::fixed(10.0));
+        timer.advance(0.09);
+        timer.reset();
+        assert_eq!(timer.advance(0.05).1, 0);
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+use winit::window::Window;
+use crate::engine::window::window_config::Fullscreen;
+
+/// Handle for changing the window at runtime, passed to `Game::init`
+#[derive(Clone)]
+pub struct GameWindow {
+    window: Arc<Window>,
+}
+
+impl GameWindow {
+    pub(crate) fn new(window: Arc<Window>) -> Self {
+        Self { window }
+    }
+
+    pub fn title(&self) -> String {
+        self.window.title()
+    }
+
+    pub fn set_title(&self, title: &str) {
+        self.window.set_title(title);
+    }
+
+    pub fn fullscreen(&self) -> Fullscreen {
+        match self.window.fullscreen() {
+            None => Fullscreen::Windowed,
+            Some(winit::window::Fullscreen::Borderless(_)) => Fullscreen::Borderless,
+            Some(winit::window::Fullscreen::Exclusive(_)) => Fullscreen::Exclusive,
+        }
+    }
+
+    /// Switches fullscreen on the monitor the window is currently on
+    pub fn set_fullscreen(&self, fullscreen: Fullscreen) {
+        self.window.set_fullscreen(fullscreen.to_winit(self.window.current_monitor()));
+    }
+
+    /// Switches between windowed and borderless fullscreen
+    pub fn toggle_fullscreen(&self) {
+        match self.fullscreen() {
+            Fullscreen::Windowed => self.set_fullscreen(Fullscreen::Borderless),
+            _ => self.set_fullscreen(Fullscreen::Windowed),
+        }
+    }
+
+    /// Inner size in physical pixels
+    pub fn size(&self) -> (u32, u32) {
+        let size = self.window.inner_size();
+        (size.width, size.height)
+    }
+
+    /// Inner size in logical pixels
+    pub fn logical_size(&self) -> (f32, f32) {
+        let size = self.window.inner_size().to_logical(self.window.scale_factor());
+        (size.width, size.height)
+    }
+
+    /// Physical pixels per logical pixel reported by the display
+    pub fn scale_factor(&self) -> f64 {
+        self.window.scale_factor()
+    }
+}
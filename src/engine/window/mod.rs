@@ -0,0 +1,2 @@
+pub mod game_window;
+pub mod window_config;
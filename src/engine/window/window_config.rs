@@ -0,0 +1,158 @@
+use winit::dpi::{LogicalSize, PhysicalPosition};
+use winit::event_loop::ActiveEventLoop;
+use winit::monitor::MonitorHandle;
+use winit::window::{Icon, WindowAttributes};
+use crate::engine::renderer::texture::Image;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Fullscreen {
+    #[default]
+    Windowed,
+    /// Window covering the whole monitor, switches instantly
+    Borderless,
+    /// Takes over the display with its largest video mode, falls back to `Borderless`
+    Exclusive,
+}
+
+impl Fullscreen {
+    pub(crate) fn to_winit(self, monitor: Option<MonitorHandle>) -> Option<winit::window::Fullscreen> {
+        match self {
+            Fullscreen::Windowed => None,
+            Fullscreen::Borderless => Some(winit::window::Fullscreen::Borderless(monitor)),
+            Fullscreen::Exclusive => {
+                let mode = monitor.as_ref().and_then(|monitor| {
+                    monitor.video_modes().max_by_key(|mode| {
+                        let size = mode.size();
+                        (size.width * size.height, mode.refresh_rate_millihertz())
+                    })
+                });
+
+                match mode {
+                    Some(mode) => Some(winit::window::Fullscreen::Exclusive(mode)),
+                    None => Some(winit::window::Fullscreen::Borderless(monitor)),
+                }
+            }
+        }
+    }
+}
+
+/// Window settings, provided by `Game::window_config`
+///
+/// ```ignore
+/// WindowConfig::new("My game").size(1280, 720).min_size(640, 360)
+/// ```
+#[derive(Clone, Debug)]
+pub struct WindowConfig {
+    pub title: String,
+    /// Inner size in logical pixels, `None` lets the platform decide
+    pub size: Option<(u32, u32)>,
+    pub min_size: Option<(u32, u32)>,
+    pub max_size: Option<(u32, u32)>,
+    pub resizable: bool,
+    pub fullscreen: Fullscreen,
+    pub decorations: bool,
+    pub icon: Option<Image>,
+    /// Outer position in physical pixels, `None` lets the platform decide
+    pub position: Option<(i32, i32)>,
+    /// Draw and report the cursor in logical pixels, so the game looks the same size on
+    /// HiDPI displays. When off, one unit is one physical pixel
+    pub hidpi_scaling: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: "Latent".to_string(),
+            size: None,
+            min_size: None,
+            max_size: None,
+            resizable: true,
+            fullscreen: Fullscreen::Windowed,
+            decorations: true,
+            icon: None,
+            position: None,
+            hidpi_scaling: true,
+        }
+    }
+}
+
+impl WindowConfig {
+    pub fn new(title: &str) -> Self {
+        Self { title: title.to_string(), ..Self::default() }
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    pub fn min_size(mut self, width: u32, height: u32) -> Self {
+        self.min_size = Some((width, height));
+        self
+    }
+
+    pub fn max_size(mut self, width: u32, height: u32) -> Self {
+        self.max_size = Some((width, height));
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn fullscreen(mut self, fullscreen: Fullscreen) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// Window and taskbar icon, e.g. from `Image::from_png_file`
+    pub fn icon(mut self, icon: Image) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn position(mut self, x: i32, y: i32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    pub fn hidpi_scaling(mut self, hidpi_scaling: bool) -> Self {
+        self.hidpi_scaling = hidpi_scaling;
+        self
+    }
+
+    pub(crate) fn attributes(&self, event_loop: &ActiveEventLoop) -> WindowAttributes {
+        let mut attributes = WindowAttributes::default()
+            .with_title(self.title.clone())
+            .with_resizable(self.resizable)
+            .with_decorations(self.decorations)
+            .with_fullscreen(self.fullscreen.to_winit(event_loop.primary_monitor()));
+
+        if let Some((width, height)) = self.size {
+            attributes = attributes.with_inner_size(LogicalSize::new(width, height));
+        }
+        if let Some((width, height)) = self.min_size {
+            attributes = attributes.with_min_inner_size(LogicalSize::new(width, height));
+        }
+        if let Some((width, height)) = self.max_size {
+            attributes = attributes.with_max_inner_size(LogicalSize::new(width, height));
+        }
+        if let Some((x, y)) = self.position {
+            attributes = attributes.with_position(PhysicalPosition::new(x, y));
+        }
+        if let Some(icon) = &self.icon {
+            match Icon::from_rgba(icon.pixels.clone(), icon.width, icon.height) {
+                Ok(icon) => attributes = attributes.with_window_icon(Some(icon)),
+                Err(e) => eprintln!("Invalid window icon: {e}"),
+            }
+        }
+
+        attributes
+    }
+}
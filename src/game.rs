@@ -0,0 +1,54 @@
+use winit::event::WindowEvent;
+use crate::engine::renderer::renderer::Renderer;
+use crate::engine::events::keyboard::ButtonState::InputManager;
+use crate::engine::time::frame_config::FrameConfig;
+use crate::engine::time::time_step::TimeStep;
+use crate::engine::window::game_window::GameWindow;
+use crate::engine::window::window_config::WindowConfig;
+
+/// Entry point for user code driven by `App`.
+///
+/// Every method except `draw` has an empty default, so a game only
+/// implements the hooks it actually needs.
+pub trait Game {
+    /// Called once, after the window and renderer have been created.
+    /// Keep a clone of `window` to change the title or fullscreen mode later
+    fn init(&mut self, _renderer: &mut Renderer, _window: &GameWindow) {}
+
+    /// Read once when the app is created
+    fn window_config(&self) -> WindowConfig {
+        WindowConfig::default()
+    }
+
+    /// Read once when the app is created
+    fn time_step(&self) -> TimeStep {
+        TimeStep::Variable
+    }
+
+    /// Read once when the app is created
+    fn frame_config(&self) -> FrameConfig {
+        FrameConfig::default()
+    }
+
+    /// Called every frame before `draw`, or every tick with `TimeStep::Fixed`,
+    /// after input states have been advanced. Input is read once per frame, so with
+    /// several ticks in one frame only the first sees its presses and releases.
+    /// Input is mutable so the game can control recording, replay and the cursor
+    fn update(&mut self, _delta_time: f32, _input: &mut InputManager) {}
+
+    /// Called every frame between `Renderer::begin_frame` and `Renderer::end_frame`.
+    /// With a fixed time step `alpha` is how far real time is between the last two ticks,
+    /// for interpolating positions, otherwise it's always 1.0
+    fn draw(&mut self, renderer: &mut Renderer, alpha: f32);
+
+    /// Called for every window event before the engine handles it
+    fn on_event(&mut self, _event: &WindowEvent) {}
+
+    /// Called when a frame couldn't be drawn. The engine reconfigures the surface after
+    /// `Lost` or `Outdated` unless the window is minimized, skips the frame on `Timeout`
+    /// and exits on `OutOfMemory`
+    fn on_surface_error(&mut self, _error: &wgpu::SurfaceError) {}
+
+    /// Called once when the event loop is exiting
+    fn shutdown(&mut self) {}
+}
@@ -0,0 +1,33 @@
+pub mod app;
+pub mod engine;
+pub mod game;
+
+pub use app::App;
+pub use game::Game;
+pub use engine::error::EngineError;
+pub use engine::events::actions::action_map::{ActionMap, Axis2DBinding, AxisBinding, Binding};
+pub use engine::events::actions::bindings_config::{BindingConflict, BindingsError};
+pub use engine::events::actions::combos::{Chord, ComboDetector, ComboEvent, ComboKind, Sequence};
+pub use engine::events::recording::input_recording::{InputEvent, InputRecording, RecordedEvent};
+pub use engine::events::keyboard::ButtonState::{ButtonState, InputManager, Key, MouseButton};
+pub use engine::events::keyboard::text_input::{Preedit, TextEvent};
+pub use engine::math::{rect::Rect, transform::Transform2D, vector2::Vector2, vector2int::Vector2Int};
+pub use engine::time::{frame_config::{FrameConfig, PresentMode, RedrawMode}, time_step::TimeStep};
+pub use engine::renderer::{blend::BlendMode, camera::Camera2D, colors::Colors, font::{Font, TextAlign, TextLayout}, offscreen::OffscreenTarget, renderer::{Renderer, RenderStats, Vertex, MAX_CHUNK_VERTICES}, stroke::{LineCap, LineJoin, StrokeStyle}, tessellate::FillRule, texture::{Image, Texture}};
+pub use engine::window::{game_window::GameWindow, window_config::{Fullscreen, WindowConfig}};
+
+use winit::event_loop::EventLoop;
+
+/// Creates the event loop and runs `game` until the window is closed.
+/// Fails if the window or GPU couldn't be set up, or the GPU ran out of memory
+pub fn run<G: Game>(game: G) -> Result<(), EngineError> {
+    let event_loop = EventLoop::new()?;
+
+    let mut app = App::new(game);
+    event_loop.run_app(&mut app)?;
+
+    match app.take_error() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}